@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::error::Error;
+use crate::centros::{Sector, Celda};
+use crate::zipf;
+
+pub struct Acumulador {
+    n: usize,
+    media: f64,
+    m2: f64,
+}
+
+impl Acumulador {
+    pub fn new() -> Self {
+        Acumulador {
+            n: 0,
+            media: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    pub fn agregar(&mut self, valor: f64) {
+        if !valor.is_finite() {
+            return
+        }
+
+        self.n += 1;
+        let delta = valor - self.media;
+        self.media += delta / self.n as f64;
+        self.m2 += delta * (valor - self.media);
+    }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn media(&self) -> f64 {
+        match self.n {
+            0 => f64::NAN,
+            _ => self.media
+        }
+    }
+
+    pub fn varianza(&self) -> f64 {
+        match self.n {
+            0 | 1 => f64::NAN,
+            _ => self.m2 / (self.n - 1) as f64
+        }
+    }
+}
+
+pub fn tamanos_sector(celdas: &HashMap<String, Celda>, sector: &Sector) -> Vec<f64> {
+    celdas.iter().filter_map(|(_, celda)| {
+        celda.size_of_activity(sector).ok()
+    }).collect()
+}
+
+pub fn sobrevivientes(tamanos: &[f64], umbral: f64) -> usize {
+    tamanos.iter().filter(|size| **size > umbral).count()
+}
+
+pub fn vivos(tamanos: &[f64], umbral: f64) -> Vec<f64> {
+    tamanos.iter().cloned().filter(|size| *size > umbral).collect()
+}
+
+pub fn participacion_maxima(tamanos: &[f64], umbral: f64) -> f64 {
+    let vivos = vivos(tamanos, umbral);
+    let total: f64 = vivos.iter().sum();
+    let maximo = vivos.iter().cloned().fold(f64::NAN, f64::max);
+
+    maximo / total
+}
+
+pub fn pendiente_rango_tamano(tamanos: &[f64], umbral: f64) -> f64 {
+    match zipf::pendiente_ols(tamanos, umbral) {
+        Some(pendiente) => pendiente,
+        None => f64::NAN
+    }
+}
+
+pub struct Convergencia {
+    tolerancia: f64,
+    previos: HashMap<String, f64>,
+    ultimo_cambio: Option<usize>,
+    iteraciones: usize,
+}
+
+impl Convergencia {
+    pub fn new(tolerancia: f64) -> Self {
+        Convergencia {
+            tolerancia: tolerancia,
+            previos: HashMap::new(),
+            ultimo_cambio: None,
+            iteraciones: 0,
+        }
+    }
+
+    pub fn registrar(&mut self, t: usize, celdas: &HashMap<String, Celda>, sector: &Sector) {
+
+        let actuales: HashMap<String, f64> = celdas.iter().filter_map(|(cve, celda)| {
+            match celda.size_of_activity(sector) {
+                Ok(size) => Some((cve.to_owned(), size)),
+                _ => None
+            }
+        }).collect();
+
+        if !self.previos.is_empty() {
+            let escala = actuales.values().cloned().fold(0.0, f64::max);
+            let cambio = actuales.iter().any(|(cve, size)| {
+                match self.previos.get(cve) {
+                    Some(previo) => !((size - previo).abs() <= self.tolerancia * escala),
+                    None => true
+                }
+            });
+
+            if cambio {
+                self.ultimo_cambio = Some(t);
+            }
+        } else {
+            self.ultimo_cambio = Some(t);
+        }
+
+        self.previos = actuales;
+        self.iteraciones = t + 1;
+    }
+
+    pub fn tiempo(&self) -> Option<usize> {
+        match self.ultimo_cambio {
+            Some(t) if t + 1 == self.iteraciones => None,
+            Some(t) => Some(t + 1),
+            None => None
+        }
+    }
+}
+
+pub struct AgregadoEta {
+    eta: f64,
+    instancias: usize,
+    sobrevivientes: Acumulador,
+    participacion: Acumulador,
+    pendiente: Acumulador,
+    convergencia: Acumulador,
+    no_convergidas: usize,
+}
+
+impl AgregadoEta {
+    pub fn new(eta: f64) -> Self {
+        AgregadoEta {
+            eta: eta,
+            instancias: 0,
+            sobrevivientes: Acumulador::new(),
+            participacion: Acumulador::new(),
+            pendiente: Acumulador::new(),
+            convergencia: Acumulador::new(),
+            no_convergidas: 0,
+        }
+    }
+}
+
+pub struct AgregadoEnsamble {
+    umbral: f64,
+    etas: Vec<AgregadoEta>,
+}
+
+impl AgregadoEnsamble {
+    pub fn new(umbral: f64) -> Self {
+        AgregadoEnsamble {
+            umbral: umbral,
+            etas: Vec::new(),
+        }
+    }
+
+    pub fn agregar_instancia(&mut self, eta: f64, celdas: &HashMap<String, Celda>, sector: &Sector, convergencia: &Convergencia) {
+
+        let tamanos = tamanos_sector(celdas, sector);
+        let umbral = self.umbral;
+
+        let posicion = match self.etas.iter().position(|agregado| (agregado.eta - eta).abs() < 1e-9) {
+            Some(posicion) => posicion,
+            None => {
+                self.etas.push(AgregadoEta::new(eta));
+                self.etas.len() - 1
+            }
+        };
+
+        let agregado = &mut self.etas[posicion];
+        agregado.instancias += 1;
+
+        agregado.sobrevivientes.agregar(sobrevivientes(&tamanos, umbral) as f64);
+        agregado.participacion.agregar(participacion_maxima(&tamanos, umbral));
+        agregado.pendiente.agregar(pendiente_rango_tamano(&tamanos, umbral));
+
+        match convergencia.tiempo() {
+            Some(t) => agregado.convergencia.agregar(t as f64),
+            None => agregado.no_convergidas += 1
+        }
+    }
+
+    pub fn escribir(&mut self, ruta: &str) -> Result<(), Box<dyn Error>> {
+
+        self.etas.sort_by(|a, b| a.eta.total_cmp(&b.eta));
+
+        let mut wtr = csv::Writer::from_path(ruta)?;
+
+        wtr.write_record([
+            "eta", "instancias",
+            "sobrevivientes_n", "sobrevivientes_media", "sobrevivientes_var",
+            "participacion_max_n", "participacion_max_media", "participacion_max_var",
+            "pendiente_rango_n", "pendiente_rango_media", "pendiente_rango_var",
+            "convergencia_n", "convergencia_media", "convergencia_var",
+            "no_convergidas",
+        ])?;
+
+        for agregado in self.etas.iter() {
+            wtr.write_record(&[
+                format!("{:.4}", agregado.eta),
+                agregado.instancias.to_string(),
+                agregado.sobrevivientes.n().to_string(),
+                agregado.sobrevivientes.media().to_string(),
+                agregado.sobrevivientes.varianza().to_string(),
+                agregado.participacion.n().to_string(),
+                agregado.participacion.media().to_string(),
+                agregado.participacion.varianza().to_string(),
+                agregado.pendiente.n().to_string(),
+                agregado.pendiente.media().to_string(),
+                agregado.pendiente.varianza().to_string(),
+                agregado.convergencia.n().to_string(),
+                agregado.convergencia.media().to_string(),
+                agregado.convergencia.varianza().to_string(),
+                agregado.no_convergidas.to_string(),
+            ])?;
+        };
+
+        wtr.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acumulador_coincide_con_la_formula_directa() {
+        let valores = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+        let mut acumulador = Acumulador::new();
+        for valor in valores.iter() {
+            acumulador.agregar(*valor);
+        };
+        acumulador.agregar(f64::NAN);
+
+        assert_eq!(acumulador.n(), 8);
+        assert!((acumulador.media() - 5.0).abs() < 1e-12);
+        assert!((acumulador.varianza() - 32.0 / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn acumulador_vacio_no_tiene_media() {
+        let mut acumulador = Acumulador::new();
+        assert!(acumulador.media().is_nan());
+
+        acumulador.agregar(3.0);
+        assert_eq!(acumulador.media(), 3.0);
+        assert!(acumulador.varianza().is_nan());
+    }
+}
@@ -0,0 +1,311 @@
+use std::error::Error;
+use rand::Rng;
+use crate::simulacion::{self, Escenario, Corrida};
+use crate::muestreo::Punto;
+use crate::utilities;
+
+pub struct PuntoBarrido {
+    pub eta: f64,
+    pub valores: Vec<f64>,
+}
+
+impl PuntoBarrido {
+    pub fn validos(&self) -> Vec<f64> {
+        self.valores.iter().cloned().filter(|valor| valor.is_finite()).collect()
+    }
+
+    pub fn excluidos(&self) -> usize {
+        self.valores.iter().filter(|valor| !valor.is_finite()).count()
+    }
+
+    pub fn media(&self) -> f64 {
+        let validos = self.validos();
+
+        validos.iter().sum::<f64>() / validos.len() as f64
+    }
+
+    pub fn error(&self) -> f64 {
+        let validos = self.validos();
+        let n = validos.len() as f64;
+
+        if n < 2.0 {
+            return f64::NAN
+        }
+
+        let media = self.media();
+        let varianza = validos.iter().map(|v| (v - media) * (v - media)).sum::<f64>() / (n - 1.0);
+
+        (varianza / n).sqrt()
+    }
+}
+
+pub struct Barrido {
+    pub puntos: Vec<PuntoBarrido>,
+    pub critico: Option<f64>,
+    pub error_critico: f64,
+}
+
+impl Barrido {
+    pub fn excluidos(&self) -> usize {
+        self.puntos.iter().map(|punto| punto.excluidos()).sum()
+    }
+}
+
+const SIGNIFICANCIA: f64 = 3.0;
+
+fn evaluar(escenario: &Escenario, eta: f64, instancias: usize, semilla: u64, orden: fn(&Escenario, &Corrida) -> f64) -> PuntoBarrido {
+
+    let escenario = escenario.con_eta(eta);
+
+    let valores = (0..instancias).map(|i| {
+        let mut rng = utilities::generador(semilla + i as u64);
+        let corrida = escenario.correr(&mut rng);
+
+        orden(&escenario, &corrida)
+    }).collect();
+
+    PuntoBarrido {
+        eta: eta,
+        valores: valores,
+    }
+}
+
+fn curva(puntos: &[PuntoBarrido]) -> (Vec<f64>, Vec<f64>) {
+    puntos.iter().filter(|punto| punto.media().is_finite()).map(|punto| (punto.eta, punto.media())).unzip()
+}
+
+fn significativo(puntos: &[PuntoBarrido]) -> bool {
+
+    let validos: Vec<&PuntoBarrido> = puntos.iter().filter(|punto| punto.media().is_finite()).collect();
+
+    let maximo = validos.iter().max_by(|a, b| a.media().total_cmp(&b.media()));
+    let minimo = validos.iter().min_by(|a, b| a.media().total_cmp(&b.media()));
+
+    match (maximo, minimo) {
+        (Some(maximo), Some(minimo)) => {
+            let ruido = (maximo.error() * maximo.error() + minimo.error() * minimo.error()).sqrt();
+            maximo.media() - minimo.media() > SIGNIFICANCIA * ruido
+        },
+        _ => false
+    }
+}
+
+fn cambio_maximo(medias: &[f64]) -> Option<usize> {
+
+    let mut mejor: Option<(usize, f64)> = None;
+
+    for k in 0..medias.len().saturating_sub(1) {
+        let cambio = (medias[k + 1] - medias[k]).abs();
+
+        match mejor {
+            Some((_, maximo)) if maximo >= cambio => {},
+            _ => mejor = Some((k, cambio))
+        }
+    };
+
+    mejor.map(|(k, _)| k)
+}
+
+fn cruce(etas: &[f64], medias: &[f64]) -> Option<f64> {
+
+    let maximo = medias.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let minimo = medias.iter().cloned().fold(f64::INFINITY, f64::min);
+    let nivel = 0.5 * (maximo + minimo);
+
+    let mut mejor: Option<(f64, f64)> = None;
+
+    for k in 0..medias.len().saturating_sub(1) {
+        let cambio = (medias[k + 1] - medias[k]).abs();
+
+        if cambio == 0.0 || (medias[k] - nivel) * (medias[k + 1] - nivel) > 0.0 {
+            continue
+        }
+
+        let eta = etas[k] + (nivel - medias[k]) / (medias[k + 1] - medias[k]) * (etas[k + 1] - etas[k]);
+
+        match mejor {
+            Some((_, maximo)) if maximo >= cambio => {},
+            _ => mejor = Some((eta, cambio))
+        }
+    };
+
+    mejor.map(|(eta, _)| eta)
+}
+
+pub fn barrido_eta(escenario: &Escenario, etas: &[f64], instancias: usize, refinamientos: usize, semilla: u64, 
+    orden: fn(&Escenario, &Corrida) -> f64) -> Result<Barrido, Box<dyn Error>> {
+
+    if etas.len() < 2 {
+        return Err(From::from("El barrido necesita al menos dos valores de eta"))
+    }
+
+    let mut puntos: Vec<PuntoBarrido> = etas.iter().map(|eta| {
+        println!("eta = {:.4}", eta);
+        evaluar(escenario, *eta, instancias, semilla, orden)
+    }).collect();
+
+    for _ in 0..refinamientos {
+        puntos.sort_by(|a, b| a.eta.total_cmp(&b.eta));
+
+        let (etas, medias) = curva(&puntos);
+
+        let k = match cambio_maximo(&medias) {
+            Some(k) => k,
+            None => break
+        };
+
+        for fraccion in [0.25, 0.5, 0.75].iter() {
+            let eta = etas[k] + fraccion * (etas[k + 1] - etas[k]);
+            println!("eta = {:.4}", eta);
+            puntos.push(evaluar(escenario, eta, instancias, semilla, orden));
+        };
+    };
+
+    puntos.sort_by(|a, b| a.eta.total_cmp(&b.eta));
+
+    let (etas, medias) = curva(&puntos);
+
+    let critico = match significativo(&puntos) {
+        true => cruce(&etas, &medias),
+        false => None
+    };
+
+    if critico.is_none() {
+        return Ok(Barrido {
+            puntos: puntos,
+            critico: None,
+            error_critico: f64::NAN,
+        })
+    }
+
+    let mut rng = utilities::generador(semilla);
+    let replicas = 200;
+
+    let validos: Vec<Vec<f64>> = puntos.iter().map(|punto| punto.validos()).filter(|validos| !validos.is_empty()).collect();
+
+    let criticos: Vec<f64> = (0..replicas).filter_map(|_| {
+        let medias: Vec<f64> = validos.iter().map(|valores| {
+            let n = valores.len();
+            (0..n).map(|_| valores[rng.gen_range(0..n)]).sum::<f64>() / n as f64
+        }).collect();
+
+        cruce(&etas, &medias)
+    }).collect();
+
+    let media_criticos = criticos.iter().sum::<f64>() / criticos.len() as f64;
+    let error_critico = (criticos.iter().map(|c| (c - media_criticos) * (c - media_criticos)).sum::<f64>() 
+        / (criticos.len() as f64 - 1.0)).sqrt();
+
+    Ok(Barrido {
+        puntos: puntos,
+        critico: critico,
+        error_critico: error_critico,
+    })
+}
+
+pub fn escribir_barrido(barrido: &Barrido, ruta: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(ruta)?;
+
+    wtr.write_record(["eta", "instancias", "excluidos", "media", "error"])?;
+
+    for punto in barrido.puntos.iter() {
+        wtr.write_record(&[
+            punto.eta.to_string(),
+            punto.valores.len().to_string(),
+            punto.excluidos().to_string(),
+            punto.media().to_string(),
+            punto.error().to_string(),
+        ])?;
+    };
+
+    wtr.flush()?;
+
+    Ok(())
+}
+
+pub struct ResultadoPunto {
+    pub punto: Punto,
+    pub metricas: Vec<Vec<(&'static str, f64)>>,
+}
+
+impl ResultadoPunto {
+    pub fn media(&self, metrica: &str) -> f64 {
+        let valores: Vec<f64> = self.metricas.iter().filter_map(|instancia| {
+            instancia.iter().find(|(nombre, _)| *nombre == metrica).map(|(_, valor)| *valor)
+        }).filter(|valor| valor.is_finite()).collect();
+
+        valores.iter().sum::<f64>() / valores.len() as f64
+    }
+
+    pub fn invalidas(&self) -> usize {
+        self.metricas.iter().filter(|instancia| {
+            instancia.iter().all(|(_, valor)| valor.is_nan())
+        }).count()
+    }
+}
+
+pub fn corridas_invalidas(resultados: &[ResultadoPunto]) -> usize {
+    resultados.iter().map(|resultado| resultado.invalidas()).sum()
+}
+
+pub fn barrido_parametros(escenario: &Escenario, puntos: &[Punto], instancias: usize, semilla: u64) 
+    -> Result<Vec<ResultadoPunto>, Box<dyn Error>> {
+
+    let mut resultados = Vec::new();
+
+    for (k, punto) in puntos.iter().enumerate() {
+        println!("punto = {} de {}", k + 1, puntos.len());
+
+        let mut escenario_punto = escenario.clone();
+        for (nombre, valor) in punto.iter() {
+            escenario_punto.set_param(nombre, *valor)?;
+        };
+
+        let metricas = (0..instancias).map(|i| {
+            let mut rng = utilities::generador(semilla + i as u64);
+            let corrida = escenario_punto.correr(&mut rng);
+
+            simulacion::metricas(&escenario_punto, &corrida)
+        }).collect();
+
+        resultados.push(ResultadoPunto {
+            punto: punto.clone(),
+            metricas: metricas,
+        });
+    };
+
+    Ok(resultados)
+}
+
+pub fn escribir_resultados(resultados: &[ResultadoPunto], ruta: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(ruta)?;
+
+    let primero = match resultados.first() {
+        Some(primero) => primero,
+        None => return Err(From::from("No hay resultados que escribir"))
+    };
+
+    let mut encabezado = vec![String::from("punto"), String::from("instancia")];
+    encabezado.extend(primero.punto.iter().map(|(nombre, _)| nombre.to_owned()));
+    encabezado.push(String::from("metrica"));
+    encabezado.push(String::from("valor"));
+
+    wtr.write_record(&encabezado)?;
+
+    for (k, resultado) in resultados.iter().enumerate() {
+        for (i, instancia) in resultado.metricas.iter().enumerate() {
+            for (metrica, valor) in instancia.iter() {
+                let mut registro = vec![k.to_string(), i.to_string()];
+                registro.extend(resultado.punto.iter().map(|(_, valor)| valor.to_string()));
+                registro.push(metrica.to_string());
+                registro.push(valor.to_string());
+
+                wtr.write_record(&registro)?;
+            };
+        };
+    };
+
+    wtr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fs::File;
+use crate::simulacion::Escenario;
+use crate::utilities;
+
+pub enum Regimen {
+    Convergente,
+    Periodico(usize),
+    Relajando,
+    NoConvergente,
+}
+
+impl Regimen {
+    pub fn nombre(&self) -> &str {
+        match self {
+            Regimen::Convergente => "convergente",
+            Regimen::Periodico(_) => "periodico",
+            Regimen::Relajando => "relajando",
+            Regimen::NoConvergente => "no_convergente",
+        }
+    }
+
+    pub fn periodo(&self) -> usize {
+        match self {
+            Regimen::Convergente => 1,
+            Regimen::Periodico(periodo) => *periodo,
+            Regimen::Relajando => 0,
+            Regimen::NoConvergente => 0,
+        }
+    }
+}
+
+fn diferencia(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).abs()).fold(0.0, f64::max)
+}
+
+pub fn clasificar(historia: &[Vec<f64>], tolerancia: f64, periodo_max: usize) -> Regimen {
+
+    let escala = historia.iter().flat_map(|fila| fila.iter()).map(|size| size.abs()).fold(0.0, f64::max);
+    let limite = tolerancia * escala.max(1.0);
+
+    for periodo in 1..=periodo_max {
+        if historia.len() <= periodo {
+            break
+        }
+
+        let repetida = (periodo..historia.len()).all(|t| {
+            diferencia(&historia[t], &historia[t - periodo]) <= limite
+        });
+
+        if repetida {
+            return match periodo {
+                1 => Regimen::Convergente,
+                _ => Regimen::Periodico(periodo)
+            }
+        }
+    };
+
+    let cambios: Vec<f64> = (1..historia.len()).map(|t| diferencia(&historia[t], &historia[t - 1])).collect();
+    let contrae = cambios.windows(2).all(|par| par[1] <= par[0] + limite);
+
+    match contrae && !cambios.is_empty() {
+        true => Regimen::Relajando,
+        false => Regimen::NoConvergente
+    }
+}
+
+pub struct SalidaBifurcacion {
+    umbral: f64,
+    tolerancia: f64,
+    periodo_max: usize,
+    tamanos: csv::Writer<File>,
+    regimenes: csv::Writer<File>,
+}
+
+impl SalidaBifurcacion {
+    pub fn get_salida_bifurcacion(ruta_tamanos: &str, ruta_regimenes: &str, umbral: f64, tolerancia: f64, periodo_max: usize) 
+        -> Result<SalidaBifurcacion, Box<dyn Error>> {
+
+        let mut tamanos = csv::Writer::from_path(ruta_tamanos)?;
+        tamanos.write_record(["eta", "instancia", "iteracion", "CVE", "size"])?;
+
+        let mut regimenes = csv::Writer::from_path(ruta_regimenes)?;
+        regimenes.write_record(["eta", "instancia", "regimen", "periodo", "sobrevivientes"])?;
+
+        Ok(SalidaBifurcacion {
+            umbral: umbral,
+            tolerancia: tolerancia,
+            periodo_max: periodo_max,
+            tamanos: tamanos,
+            regimenes: regimenes,
+        })
+    }
+
+    pub fn escribir_eta(&mut self, escenario: &Escenario, eta: f64, instancias: usize, transitorio: usize, semilla: u64) 
+        -> Result<(), Box<dyn Error>> {
+
+        let escenario = escenario.con_eta(eta);
+
+        for i in 0..instancias {
+            let mut rng = utilities::generador(semilla + i as u64);
+            let (centros, historia) = escenario.correr_historia(&mut rng, transitorio);
+
+            let regimen = clasificar(&historia, self.tolerancia, self.periodo_max);
+
+            for (k, fila) in historia.iter().enumerate() {
+                for (cve, size) in centros.iter().zip(fila.iter()) {
+                    if *size > self.umbral {
+                        self.tamanos.write_record(&[
+                            eta.to_string(),
+                            i.to_string(),
+                            (transitorio + k).to_string(),
+                            cve.to_owned(),
+                            size.to_string(),
+                        ])?;
+                    }
+                };
+            };
+
+            let sobrevivientes = match historia.last() {
+                Some(fila) => fila.iter().filter(|size| **size > self.umbral).count(),
+                None => 0
+            };
+
+            self.regimenes.write_record(&[
+                eta.to_string(),
+                i.to_string(),
+                regimen.nombre().to_owned(),
+                regimen.periodo().to_string(),
+                sobrevivientes.to_string(),
+            ])?;
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.tamanos.flush()?;
+        self.regimenes.flush()?;
+
+        Ok(())
+    }
+}
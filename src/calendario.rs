@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use crate::centros::{Sector, Celda};
+use crate::agregados;
+
+pub enum Frecuencia {
+    Cada(usize),
+    Logaritmica(f64),
+    Final,
+    CambioSobrevivientes(f64),
+}
+
+pub struct Calendario {
+    frecuencia: Frecuencia,
+    iteraciones: usize,
+    conteos: HashMap<String, usize>,
+    siguiente: usize,
+}
+
+impl Calendario {
+    pub fn new(frecuencia: Frecuencia, iteraciones: usize) -> Self {
+        Calendario {
+            frecuencia: frecuencia,
+            iteraciones: iteraciones,
+            conteos: HashMap::new(),
+            siguiente: 1,
+        }
+    }
+
+    pub fn es_continuo(&self) -> bool {
+        match self.frecuencia {
+            Frecuencia::Cada(1) => true,
+            _ => false
+        }
+    }
+
+    pub fn toca(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) -> bool {
+
+        let ultima = t + 1 == self.iteraciones;
+
+        match self.frecuencia {
+            Frecuencia::Cada(k) => t.is_multiple_of(k.max(1)) || ultima,
+            Frecuencia::Logaritmica(base) => {
+                let avanzar = |potencia: usize| ((potencia as f64 * base).round() as usize).max(potencia + 1);
+
+                if t == 0 {
+                    self.siguiente = avanzar(1);
+                    return true
+                }
+
+                let toca = t + 1 >= self.siguiente;
+
+                while self.siguiente <= t + 1 {
+                    self.siguiente = avanzar(self.siguiente);
+                };
+
+                toca || ultima
+            },
+            Frecuencia::Final => ultima,
+            Frecuencia::CambioSobrevivientes(umbral) => {
+                let mut cambio = false;
+
+                for (cve, sector) in sectores.iter() {
+                    let tamanos = agregados::tamanos_sector(celdas, sector);
+                    let conteo = agregados::sobrevivientes(&tamanos, umbral);
+
+                    if self.conteos.insert(cve.to_owned(), conteo) != Some(conteo) {
+                        cambio = true;
+                    }
+                };
+
+                cambio || ultima
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::error::Error;
+use crate::centros::{Sector, Celda};
+use crate::topologias;
+use crate::utilities;
+
+pub struct Canonico {
+    pub nombre: &'static str,
+    pub descripcion: &'static str,
+    pub n: usize,
+    pub anillo: bool,
+    pub centros: Vec<usize>,
+}
+
+impl Canonico {
+    pub fn celdas<'a>(&self, size: f64, growth_factor: f64, sector: &'a Sector) -> Result<HashMap<String, Celda<'a>>, Box<dyn Error>> {
+
+        let mut celdas = match self.anillo {
+            true => topologias::anillo(self.n, 1.0, 1.0),
+            false => topologias::linea(self.n, 1.0, 1.0)
+        };
+
+        let cves: Vec<String> = self.centros.iter().map(|i| topologias::cve_de_indices(*i, 0)).collect();
+        utilities::centers_from_vec_with_growth(&cves, size, growth_factor, &mut celdas, sector)?;
+
+        Ok(celdas)
+    }
+}
+
+pub fn canonicos() -> Vec<Canonico> {
+    vec![
+        Canonico {
+            nombre: "dos_simetricos",
+            descripcion: "Dos centros simétricos en un segmento de 21 celdas",
+            n: 21,
+            anillo: false,
+            centros: vec![5, 15],
+        },
+        Canonico {
+            nombre: "dos_asimetricos",
+            descripcion: "Dos centros desplazados del centro en un segmento de 21 celdas",
+            n: 21,
+            anillo: false,
+            centros: vec![5, 12],
+        },
+        Canonico {
+            nombre: "tres_en_linea",
+            descripcion: "Tres centros equiespaciados en un segmento de 31 celdas",
+            n: 31,
+            anillo: false,
+            centros: vec![5, 15, 25],
+        },
+        Canonico {
+            nombre: "dos_en_anillo",
+            descripcion: "Dos centros opuestos en un anillo de 20 celdas",
+            n: 20,
+            anillo: true,
+            centros: vec![0, 10],
+        },
+        Canonico {
+            nombre: "tres_en_anillo",
+            descripcion: "Tres centros equiespaciados en un anillo de 30 celdas",
+            n: 30,
+            anillo: true,
+            centros: vec![0, 10, 20],
+        },
+    ]
+}
+
+pub fn buscar(nombre: &str) -> Result<Canonico, Box<dyn Error>> {
+    match canonicos().into_iter().find(|canonico| canonico.nombre == nombre) {
+        Some(canonico) => Ok(canonico),
+        None => {
+            let nombres: Vec<&str> = canonicos().iter().map(|canonico| canonico.nombre).collect();
+            Err(From::from(format!("No existe el escenario canónico {} (disponibles: {})", nombre, nombres.join(", "))))
+        }
+    }
+}
@@ -1,5 +1,103 @@
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use crate::costos::Faltantes;
+use crate::redes::Modo;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum FuenteMetrica {
+    Toro { ancho: f64, alto: f64 },
+    Red { nodos: String, aristas: String, dirigida: bool, modo: Modo, velocidad_acceso: f64 },
+    Costos { ruta: String, ancho: Option<usize>, faltantes: Faltantes },
+    Desconocida,
+}
+
+pub trait Metrica: Send + Sync {
+    fn distancia(&self, origen: &Celda, destino: &Celda) -> f64;
+
+    fn periodo(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    fn fuente(&self) -> FuenteMetrica {
+        FuenteMetrica::Desconocida
+    }
+}
+
+pub struct Toro {
+    ancho: f64,
+    alto: f64,
+}
+
+impl Toro {
+    pub fn new(ancho: f64, alto: f64) -> Self {
+        Toro {
+            ancho: ancho,
+            alto: alto,
+        }
+    }
+}
+
+impl Metrica for Toro {
+    fn distancia(&self, origen: &Celda, destino: &Celda) -> f64 {
+        let dx = (origen.x - destino.x).abs() % self.ancho;
+        let dy = (origen.y - destino.y).abs() % self.alto;
+
+        let parte_x = dx.min(self.ancho - dx);
+        let parte_y = dy.min(self.alto - dy);
+
+        (parte_x * parte_x + parte_y * parte_y).sqrt()
+    }
+
+    fn periodo(&self) -> Option<(f64, f64)> {
+        Some((self.ancho, self.alto))
+    }
+
+    fn fuente(&self) -> FuenteMetrica {
+        FuenteMetrica::Toro { ancho: self.ancho, alto: self.alto }
+    }
+}
+
+pub struct Tabla {
+    indices: HashMap<String, usize>,
+    n: usize,
+    valores: Vec<f64>,
+}
+
+impl Tabla {
+    pub fn new(cves: &[String], valores: Vec<f64>) -> Result<Tabla, Box<dyn Error>> {
+        let n = cves.len();
+
+        if valores.len() != n * n {
+            return Err(From::from("La tabla de distancias no es cuadrada"))
+        }
+
+        Ok(Tabla {
+            indices: cves.iter().enumerate().map(|(i, cve)| (cve.to_owned(), i)).collect(),
+            n: n,
+            valores: valores,
+        })
+    }
+
+    pub fn indice(&self, cve: &str) -> Option<usize> {
+        self.indices.get(cve).cloned()
+    }
+
+    pub fn valor(&self, i: usize, j: usize) -> f64 {
+        self.valores[i * self.n + j]
+    }
+
+    pub fn distancia(&self, origen: &Celda, destino: &Celda) -> f64 {
+        let i = origen.indice.or_else(|| self.indice(&origen.cve));
+        let j = destino.indice.or_else(|| self.indice(&destino.cve));
+
+        match (i, j) {
+            (Some(i), Some(j)) => self.valor(i, j),
+            _ => f64::INFINITY
+        }
+    }
+}
 
 pub struct Celda<'a> {
     cve: String,
@@ -7,6 +105,8 @@ pub struct Celda<'a> {
     y: f64,
     actividades: HashMap<String, Actividad<'a>>,
     poblacion: f64,
+    metrica: Option<Arc<dyn Metrica>>,
+    indice: Option<usize>,
 }
 
 impl<'a> Celda<'a> {
@@ -19,6 +119,8 @@ impl<'a> Celda<'a> {
             y: y,
             actividades: HashMap::new(),
             poblacion: poblacion,
+            metrica: None,
+            indice: None,
         }
     }
 
@@ -38,12 +140,41 @@ impl<'a> Celda<'a> {
     }
 
     pub fn distance(&self, other: &Celda) -> f64 {
+        if let Some(metrica) = self.metrica.as_ref().filter(|metrica| metrica.periodo().is_some()) {
+            return metrica.distancia(self, other)
+        }
+
         let parte_x = (self.x - other.x) * (self.x - other.x);
         let parte_y = (self.y - other.y) * (self.y - other.y);
 
         (parte_x + parte_y).sqrt()
     }
 
+    pub fn costo(&self, other: &Celda) -> f64 {
+        match &self.metrica {
+            Some(metrica) => metrica.distancia(self, other),
+            None => self.distance(other)
+        }
+    }
+
+    pub fn set_metrica(&mut self, metrica: Arc<dyn Metrica>) {
+        self.metrica = Some(metrica);
+        self.indice = None;
+    }
+
+    pub fn set_metrica_indexada(&mut self, metrica: Arc<dyn Metrica>, indice: usize) {
+        self.metrica = Some(metrica);
+        self.indice = Some(indice);
+    }
+
+    pub fn indice(&self) -> Option<usize> {
+        self.indice
+    }
+
+    pub fn metrica(&self) -> Option<&Arc<dyn Metrica>> {
+        self.metrica.as_ref()
+    }
+
     pub fn populate(&mut self, population: f64) {
         self.poblacion = population;
     }
@@ -56,6 +187,11 @@ impl<'a> Celda<'a> {
         (self.x, self.y) 
     }
 
+    pub fn set_coordinates(&mut self, x: f64, y: f64) {
+        self.x = x;
+        self.y = y;
+    }
+
     pub fn cve(&self) -> String {
         self.cve.to_owned() 
     }
@@ -70,6 +206,10 @@ impl<'a> Celda<'a> {
         }
     }
 
+    pub fn activities(&self) -> &HashMap<String, Actividad<'a>> {
+        &self.actividades
+    }
+
     pub fn get_activity(&self, sector: &'a Sector) -> Option<&'a Actividad> {
         match self.actividades.get(&sector.cve) {
             Some(actividad) => {
@@ -99,11 +239,16 @@ impl<'a> Actividad<'a> {
         self.size
     }
 
+    pub fn growth_factor(&self) -> f64 {
+        self.growth_factor
+    }
+
     pub fn get_sector(&self) -> &'a Sector {
         self.sector
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Sector {
     cve: String,
     pop_param: f64,
@@ -122,6 +267,40 @@ impl Sector {
             ..Default::default()
         }
     }
+
+    pub fn cve(&self) -> String {
+        self.cve.to_owned()
+    }
+
+    pub fn nombre(&self) -> &str {
+        &self.cve
+    }
+
+    pub fn param(&self, nombre: &str) -> Result<f64, Box<dyn Error>> {
+        match nombre {
+            "pop_param" => Ok(self.pop_param),
+            "fixed_cost" => Ok(self.fixed_cost),
+            "returns_const" => Ok(self.returns_const),
+            "cost_exp" => Ok(self.cost_exp),
+            "p_capita_expenditure" => Ok(self.p_capita_expenditure),
+            "interaction" => Ok(self.interaction),
+            _ => Err(From::from("El sector no tiene ese parámetro"))
+        }
+    }
+
+    pub fn set_param(&mut self, nombre: &str, valor: f64) -> Result<(), Box<dyn Error>> {
+        match nombre {
+            "pop_param" => self.pop_param = valor,
+            "fixed_cost" => self.fixed_cost = valor,
+            "returns_const" => self.returns_const = valor,
+            "cost_exp" => self.cost_exp = valor,
+            "p_capita_expenditure" => self.p_capita_expenditure = valor,
+            "interaction" => self.interaction = valor,
+            _ => return Err(From::from("El sector no tiene ese parámetro"))
+        };
+
+        Ok(())
+    }
 }
 
 impl Default for Sector {
@@ -139,6 +318,10 @@ impl Default for Sector {
 }
 
 pub trait Economy<T> {
+    fn member_flux(&self, elem: &T, consumer: &T, sector: &Sector) -> Result<f64,Box<dyn Error>>;
+    fn consumer_fluxes(&self, consumer: &T, sector: &Sector) -> Vec<(String, f64)>;
+    fn consumer_flows(&self, consumer: &T, sector: &Sector) -> Vec<(String, f64)>;
+
     fn member_revenue(&self, elem: &T, sector: &Sector) -> Result<f64,Box<dyn Error>>;
     fn member_cost(&self, elem: &T, sector: &Sector) -> Result<f64,Box<dyn Error>>;
     fn member_size(&self, elem: &T, sector: &Sector) -> Result<f64, Box<dyn Error>>;
@@ -149,7 +332,71 @@ pub trait Economy<T> {
     fn evolve(&mut self, sectores: &HashMap<String, Sector>);
 }
 
+fn peso(consumidor: &Celda, centro: &Celda, size: f64, sector: &Sector) -> f64 {
+    size * consumidor.costo(centro).powf(-1.0 * sector.interaction)
+}
+
+fn denominador<'a>(celdas: &HashMap<String, Celda<'a>>, consumidor: &Celda<'a>, sector: &Sector) -> f64 {
+    celdas.iter().filter_map(|(_, cell)| {
+        match &cell.actividades.is_empty() {
+            true => None,
+            false => match &cell.actividades.get(&sector.cve) {
+                Some(activcxy) => {
+                    Some(peso(consumidor, cell, activcxy.size, sector))
+                },
+                None => None
+            }
+        }
+    }).sum()
+}
+
 impl<'a> Economy<Celda<'a>> for HashMap<String, Celda<'a>> {
+    fn member_flux(&self, celda: &Celda<'a>, consumidor: &Celda<'a>, sector: &Sector) -> Result<f64, Box<dyn Error>> {
+
+        let actividad = match celda.actividades.get(&sector.cve) {
+            Some(actividad) => {
+                actividad
+            },
+            None => return Err(From::from("La celda no tiene actividad para ese sector"))
+        };
+
+        if consumidor.costo(celda) == 0.0 {
+            return Ok(0.0)
+        }
+
+        let numer = peso(consumidor, celda, actividad.size, sector);
+        let denom = denominador(self, consumidor, sector);
+
+        Ok(numer/denom)
+    }
+
+    fn consumer_fluxes(&self, consumidor: &Celda<'a>, sector: &Sector) -> Vec<(String, f64)> {
+
+        let denom = denominador(self, consumidor, sector);
+
+        self.iter().filter_map(|(cve, cell)| {
+            match cell.actividades.get(&sector.cve) {
+                Some(actividad) => {
+                    let flux = match consumidor.costo(cell) == 0.0 {
+                        true => 0.0,
+                        false => peso(consumidor, cell, actividad.size, sector) / denom
+                    };
+                    Some((cve.to_owned(), flux))
+                },
+                None => None
+            }
+        }).collect()
+    }
+
+    fn consumer_flows(&self, consumidor: &Celda<'a>, sector: &Sector) -> Vec<(String, f64)> {
+
+        let gasto = consumidor.poblacion * sector.p_capita_expenditure;
+
+        self.consumer_fluxes(consumidor, sector).into_iter().map(|(cve, flux)| {
+            (cve, flux * gasto)
+        }).collect()
+    }
+
     fn member_revenue(&self, celda: &Celda<'a>, sector: &Sector) -> Result<f64, Box<dyn Error>> {
         
         use rayon::prelude::*;
@@ -161,22 +408,11 @@ impl<'a> Economy<Celda<'a>> for HashMap<String, Celda<'a>> {
             None => return Err(From::from("La celda no tiene actividad para ese sector"))
         };
 
-        let revenue: f64 = self.par_iter().filter(|(_, cellxy)| cellxy.distance(celda) != 0.0 )
+        let revenue: f64 = self.par_iter().filter(|(_, cellxy)| cellxy.costo(celda) != 0.0 )
         .map(|(_, cellxy)| {
             
-            let numer = actividad.size * cellxy.distance(celda).powf(-1.0 * sector.interaction);
-            
-            let denom: f64 = self.iter().filter_map(|(_, cell)| {
-                match &cell.actividades.is_empty() {
-                    true => None,
-                    false => match &cell.actividades.get(&sector.cve) {
-                        Some(activcxy) => {
-                            Some(activcxy.size * cell.distance(cellxy).powf(-1.0 * sector.interaction))
-                        },
-                        None => None
-                    }
-                }
-            }).sum();
+            let numer = peso(cellxy, celda, actividad.size, sector);
+            let denom = denominador(self, cellxy, sector);
 
             let flux = numer/denom;
             let population = cellxy.poblacion;
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use crate::centros::{Celda, Metrica, Tabla, FuenteMetrica};
+use crate::lectura;
+use crate::utilities;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Faltantes {
+    Rechazar,
+    Euclidiana(f64),
+    Penalizacion(f64),
+}
+
+pub struct MatrizCostos {
+    tabla: Tabla,
+    faltantes: usize,
+    ruta: String,
+    ancho: Option<usize>,
+    politica: Faltantes,
+}
+
+impl MatrizCostos {
+    pub fn leer(ruta: &str, celdas: &HashMap<String, Celda>, ancho: Option<usize>, politica: Faltantes) -> Result<MatrizCostos, Box<dyn Error>> {
+
+        match politica {
+            Faltantes::Euclidiana(factor) if !(factor > 0.0) => return Err(From::from("El factor de costo por unidad de distancia debe ser positivo")),
+            Faltantes::Penalizacion(costo) if !(costo > 0.0) => return Err(From::from("El costo de penalización debe ser positivo")),
+            _ => {}
+        };
+
+        let mut cves: Vec<String> = celdas.keys().cloned().collect();
+        cves.sort();
+
+        let indices: HashMap<&str, usize> = cves.iter().enumerate().map(|(i, cve)| (cve.as_str(), i)).collect();
+        let n = cves.len();
+
+        let mut valores = vec![f64::NAN; n * n];
+
+        let mut rdr = csv::Reader::from_path(ruta)?;
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let origen = lectura::preparar_cve(&record[0], ancho);
+            let destino = lectura::preparar_cve(&record[1], ancho);
+            let costo = record[2].parse::<f64>()?;
+
+            let (i, j) = match (indices.get(origen.as_str()), indices.get(destino.as_str())) {
+                (Some(i), Some(j)) => (*i, *j),
+                (None, _) => return Err(From::from(format!("La matriz de costos hace referencia a la celda inexistente {}", origen))),
+                (_, None) => return Err(From::from(format!("La matriz de costos hace referencia a la celda inexistente {}", destino)))
+            };
+
+            let valido = match i == j {
+                true => costo >= 0.0,
+                false => costo > 0.0
+            };
+
+            if !valido {
+                return Err(From::from(format!("El costo de {} a {} no es válido", origen, destino)))
+            }
+
+            if !valores[i * n + j].is_nan() {
+                return Err(From::from(format!("El par {} a {} está repetido", origen, destino)))
+            }
+
+            valores[i * n + j] = costo;
+        };
+
+        let mut faltantes = 0;
+
+        for i in 0..n {
+            for j in 0..n {
+                if i == j || !valores[i * n + j].is_nan() {
+                    continue
+                }
+
+                faltantes += 1;
+
+                valores[i * n + j] = match politica {
+                    Faltantes::Rechazar => return Err(From::from(format!("La matriz de costos no tiene el par {} a {}", cves[i], cves[j]))),
+                    Faltantes::Euclidiana(factor) => {
+                        let (xo, yo) = celdas[&cves[i]].coordinates();
+                        let (xd, yd) = celdas[&cves[j]].coordinates();
+                        let costo = factor * ((xo - xd) * (xo - xd) + (yo - yd) * (yo - yd)).sqrt();
+
+                        match costo > 0.0 {
+                            true => costo,
+                            false => return Err(From::from(format!("Las celdas {} y {} coinciden y no hay costo para el par", cves[i], cves[j])))
+                        }
+                    },
+                    Faltantes::Penalizacion(costo) => costo
+                };
+            };
+
+            if valores[i * n + i].is_nan() {
+                valores[i * n + i] = 0.0;
+            }
+        };
+
+        Ok(MatrizCostos {
+            tabla: Tabla::new(&cves, valores)?,
+            faltantes: faltantes,
+            ruta: String::from(ruta),
+            ancho: ancho,
+            politica: politica,
+        })
+    }
+
+    pub fn faltantes(&self) -> usize {
+        self.faltantes
+    }
+
+    pub fn costo(&self, origen: &str, destino: &str) -> Option<f64> {
+        match (self.tabla.indice(origen), self.tabla.indice(destino)) {
+            (Some(i), Some(j)) => Some(self.tabla.valor(i, j)),
+            _ => None
+        }
+    }
+
+    pub fn indices(&self, celdas: &HashMap<String, Celda>) -> HashMap<String, usize> {
+        celdas.keys().filter_map(|cve| self.tabla.indice(cve).map(|i| (cve.to_owned(), i))).collect()
+    }
+}
+
+impl Metrica for MatrizCostos {
+    fn fuente(&self) -> FuenteMetrica {
+        FuenteMetrica::Costos {
+            ruta: self.ruta.to_owned(),
+            ancho: self.ancho,
+            faltantes: self.politica,
+        }
+    }
+
+    fn distancia(&self, origen: &Celda, destino: &Celda) -> f64 {
+        self.tabla.distancia(origen, destino)
+    }
+}
+
+pub fn asignar_costos(celdas: &mut HashMap<String, Celda>, matriz: MatrizCostos) {
+    let indices = matriz.indices(celdas);
+    utilities::asignar_metrica_indexada(celdas, Arc::new(matriz), &indices);
+}
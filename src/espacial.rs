@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use crate::centros::{Sector, Celda};
+
+pub struct Ventana {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+    pub periodica: bool,
+}
+
+fn espaciado(mut valores: Vec<f64>) -> Option<f64> {
+    valores.sort_by(|a, b| a.total_cmp(b));
+
+    valores.windows(2).map(|par| par[1] - par[0]).filter(|gap| *gap > 0.0).fold(None, |minimo: Option<f64>, gap| {
+        match minimo {
+            Some(minimo) if minimo <= gap => Some(minimo),
+            _ => Some(gap)
+        }
+    })
+}
+
+impl Ventana {
+    pub fn de_celdas(celdas: &HashMap<String, Celda>) -> Self {
+        let mut ventana = Ventana {
+            x_min: f64::INFINITY,
+            x_max: f64::NEG_INFINITY,
+            y_min: f64::INFINITY,
+            y_max: f64::NEG_INFINITY,
+            periodica: false,
+        };
+
+        for (_, celda) in celdas.iter() {
+            let (x, y) = celda.coordinates();
+            ventana.x_min = ventana.x_min.min(x);
+            ventana.x_max = ventana.x_max.max(x);
+            ventana.y_min = ventana.y_min.min(y);
+            ventana.y_max = ventana.y_max.max(y);
+        };
+
+        let espaciado_x = espaciado(celdas.values().map(|celda| celda.coordinates().0).collect());
+        let espaciado_y = espaciado(celdas.values().map(|celda| celda.coordinates().1).collect());
+        let margen_x = espaciado_x.or(espaciado_y).unwrap_or(1.0) / 2.0;
+        let margen_y = espaciado_y.or(espaciado_x).unwrap_or(1.0) / 2.0;
+
+        ventana.x_min -= margen_x;
+        ventana.x_max += margen_x;
+        ventana.y_min -= margen_y;
+        ventana.y_max += margen_y;
+
+        let periodo = celdas.values().next()
+            .and_then(|celda| celda.metrica())
+            .and_then(|metrica| metrica.periodo());
+
+        if let Some((ancho, alto)) = periodo {
+            ventana.x_max = ventana.x_min + ancho;
+            ventana.y_max = ventana.y_min + alto;
+            ventana.periodica = true;
+        }
+
+        ventana
+    }
+
+    pub fn ancho(&self) -> f64 {
+        self.x_max - self.x_min
+    }
+
+    pub fn alto(&self) -> f64 {
+        self.y_max - self.y_min
+    }
+
+    pub fn area(&self) -> f64 {
+        self.ancho() * self.alto()
+    }
+}
+
+pub struct ClarkEvans {
+    pub indice: f64,
+    pub z: f64,
+}
+
+pub struct Ripley {
+    pub r: f64,
+    pub k: f64,
+    pub l: f64,
+}
+
+pub fn centros_vivos<'b, 'a>(celdas: &'b HashMap<String, Celda<'a>>, sector: &Sector, umbral: f64) -> Vec<(&'b Celda<'a>, f64)> {
+
+    let mut vivos: Vec<(&Celda, f64)> = celdas.values().filter_map(|celda| {
+        match celda.size_of_activity(sector) {
+            Ok(size) if size > umbral => Some((celda, size)),
+            _ => None
+        }
+    }).collect();
+
+    vivos.sort_by_key(|(celda, _)| celda.cve());
+
+    vivos
+}
+
+pub fn clark_evans(centros: &[(&Celda, f64)], ventana: &Ventana) -> Option<ClarkEvans> {
+
+    let n = centros.len();
+
+    if n < 2 {
+        return None
+    }
+
+    let media: f64 = centros.iter().map(|(celda, _)| {
+        centros.iter().filter(|(otra, _)| otra.cve() != celda.cve())
+            .map(|(otra, _)| celda.distance(otra))
+            .fold(f64::INFINITY, f64::min)
+    }).sum::<f64>() / n as f64;
+
+    let densidad = n as f64 / ventana.area();
+    let esperada = 0.5 / densidad.sqrt();
+    let error = 0.26136 / (n as f64 * densidad).sqrt();
+
+    Some(ClarkEvans {
+        indice: media / esperada,
+        z: (media - esperada) / error,
+    })
+}
+
+pub fn ripley(centros: &[(&Celda, f64)], ventana: &Ventana, radios: &[f64], ponderado: bool) -> Vec<Ripley> {
+
+    let area = ventana.area();
+
+    let mut pares = Vec::new();
+    let mut total = 0.0;
+
+    for (i, (celda_i, size_i)) in centros.iter().enumerate() {
+        for (j, (celda_j, size_j)) in centros.iter().enumerate() {
+            if i == j {
+                continue
+            }
+
+            let peso = match ponderado {
+                true => size_i * size_j,
+                false => 1.0
+            };
+
+            let (xi, yi) = celda_i.coordinates();
+            let (xj, yj) = celda_j.coordinates();
+            let traslape = (ventana.ancho() - (xi - xj).abs()) * (ventana.alto() - (yi - yj).abs());
+            let correccion = match (ventana.periodica, traslape > 0.0) {
+                (true, _) => 1.0,
+                (false, true) => area / traslape,
+                (false, false) => 0.0
+            };
+
+            pares.push((celda_i.distance(celda_j), peso * correccion));
+            total += peso;
+        };
+    };
+
+    radios.iter().map(|r| {
+        let k = match total > 0.0 {
+            true => area * pares.iter().filter(|(d, _)| d <= r).map(|(_, w)| w).sum::<f64>() / total,
+            false => f64::NAN
+        };
+
+        Ripley {
+            r: *r,
+            k: k,
+            l: (k / std::f64::consts::PI).sqrt(),
+        }
+    }).collect()
+}
+
+pub fn moran(centros: &[(&Celda, f64)]) -> Option<f64> {
+
+    let n = centros.len();
+
+    if n < 2 {
+        return None
+    }
+
+    let media = centros.iter().map(|(_, size)| size).sum::<f64>() / n as f64;
+    let varianza: f64 = centros.iter().map(|(_, size)| (size - media) * (size - media)).sum();
+
+    if varianza == 0.0 {
+        return None
+    }
+
+    let mut suma_pesos = 0.0;
+    let mut covarianza = 0.0;
+
+    for (i, (celda_i, size_i)) in centros.iter().enumerate() {
+        for (j, (celda_j, size_j)) in centros.iter().enumerate() {
+            let distancia = celda_i.distance(celda_j);
+
+            if i == j || distancia == 0.0 {
+                continue
+            }
+
+            let peso = 1.0 / distancia;
+            suma_pesos += peso;
+            covarianza += peso * (size_i - media) * (size_j - media);
+        };
+    };
+
+    Some((n as f64 / suma_pesos) * covarianza / varianza)
+}
+
+pub struct SalidaEspacial {
+    umbral: f64,
+    radios: Vec<f64>,
+    resumen: csv::Writer<File>,
+    ripley: csv::Writer<File>,
+}
+
+impl SalidaEspacial {
+    pub fn get_salida_espacial(ruta_resumen: &str, ruta_ripley: &str, umbral: f64, radios: Vec<f64>) -> Result<SalidaEspacial, Box<dyn Error>> {
+
+        let mut resumen = csv::Writer::from_path(ruta_resumen)?;
+        resumen.write_record(["iteracion", "sector", "centros", "clark_evans", "clark_evans_z", "moran"])?;
+
+        let mut ripley = csv::Writer::from_path(ruta_ripley)?;
+        ripley.write_record(["iteracion", "sector", "ponderado", "r", "k", "l"])?;
+
+        Ok(SalidaEspacial {
+            umbral: umbral,
+            radios: radios,
+            resumen: resumen,
+            ripley: ripley,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) 
+        -> Result<(), Box<dyn Error>> {
+
+        let ventana = Ventana::de_celdas(celdas);
+
+        let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+        cves_sectores.sort();
+
+        for cve_sector in cves_sectores {
+            let centros = centros_vivos(celdas, &sectores[cve_sector], self.umbral);
+
+            let (indice, z) = match clark_evans(&centros, &ventana) {
+                Some(ce) => (ce.indice.to_string(), ce.z.to_string()),
+                None => (String::new(), String::new())
+            };
+
+            let moran = match moran(&centros) {
+                Some(moran) => moran.to_string(),
+                None => String::new()
+            };
+
+            self.resumen.write_record(&[
+                t.to_string(),
+                cve_sector.to_owned(),
+                centros.len().to_string(),
+                indice,
+                z,
+                moran,
+            ])?;
+
+            for ponderado in [false, true].iter() {
+                for punto in ripley(&centros, &ventana, &self.radios, *ponderado) {
+                    self.ripley.write_record(&[
+                        t.to_string(),
+                        cve_sector.to_owned(),
+                        ponderado.to_string(),
+                        punto.r.to_string(),
+                        punto.k.to_string(),
+                        punto.l.to_string(),
+                    ])?;
+                };
+            };
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.resumen.flush()?;
+        self.ripley.flush()?;
+
+        Ok(())
+    }
+}
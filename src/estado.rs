@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use crate::centros::{Sector, Celda, Toro, FuenteMetrica};
+use crate::costos;
+use crate::redes;
+use crate::utilities;
+
+#[derive(Serialize, Deserialize)]
+pub struct EstadoActividad {
+    sector: String,
+    size: f64,
+    growth_factor: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EstadoCelda {
+    cve: String,
+    x: f64,
+    y: f64,
+    poblacion: f64,
+    actividades: Vec<EstadoActividad>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EstadoRng {
+    semilla: [u8; 32],
+    stream: u64,
+    posicion: u128,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Estado {
+    iteracion: usize,
+    sectores: Vec<Sector>,
+    celdas: Vec<EstadoCelda>,
+    rng: Option<EstadoRng>,
+    metrica: Option<FuenteMetrica>,
+}
+
+impl Estado {
+    pub fn capturar(iteracion: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>, rng: Option<&ChaCha8Rng>) -> Self {
+
+        let mut sectores: Vec<Sector> = sectores.values().cloned().collect();
+        sectores.sort_by_key(|sector| sector.cve());
+
+        let metrica = celdas.values().next()
+            .and_then(|celda| celda.metrica())
+            .map(|metrica| metrica.fuente());
+
+        let mut celdas: Vec<EstadoCelda> = celdas.values().map(|celda| {
+            let (x, y) = celda.coordinates();
+
+            let mut actividades: Vec<EstadoActividad> = celda.activities().iter().map(|(sector, actividad)| {
+                EstadoActividad {
+                    sector: sector.to_owned(),
+                    size: actividad.size(),
+                    growth_factor: actividad.growth_factor(),
+                }
+            }).collect();
+            actividades.sort_by(|a, b| a.sector.cmp(&b.sector));
+
+            EstadoCelda {
+                cve: celda.cve(),
+                x: x,
+                y: y,
+                poblacion: celda.population(),
+                actividades: actividades,
+            }
+        }).collect();
+        celdas.sort_by(|a, b| a.cve.cmp(&b.cve));
+
+        let rng = rng.map(|rng| {
+            EstadoRng {
+                semilla: rng.get_seed(),
+                stream: rng.get_stream(),
+                posicion: rng.get_word_pos(),
+            }
+        });
+
+        Estado {
+            iteracion: iteracion,
+            sectores: sectores,
+            celdas: celdas,
+            rng: rng,
+            metrica: metrica,
+        }
+    }
+
+    pub fn guardar(&self, ruta: &str) -> Result<(), Box<dyn Error>> {
+        let writer = BufWriter::new(File::create(ruta)?);
+        serde_json::to_writer(writer, self)?;
+
+        Ok(())
+    }
+
+    pub fn cargar(ruta: &str) -> Result<Estado, Box<dyn Error>> {
+        let reader = BufReader::new(File::open(ruta)?);
+        let estado = serde_json::from_reader(reader)?;
+
+        Ok(estado)
+    }
+
+    pub fn iteracion(&self) -> usize {
+        self.iteracion
+    }
+
+    pub fn sectores(&self) -> HashMap<String, Sector> {
+        self.sectores.iter().map(|sector| (sector.cve(), sector.clone())).collect()
+    }
+
+    pub fn celdas<'a>(&self, sectores: &'a HashMap<String, Sector>) -> Result<HashMap<String, Celda<'a>>, Box<dyn Error>> {
+
+        let mut celdas = HashMap::new();
+
+        for estado_celda in self.celdas.iter() {
+            let mut celda = Celda::new(&estado_celda.cve, estado_celda.x, estado_celda.y, estado_celda.poblacion);
+
+            for actividad in estado_celda.actividades.iter() {
+                let sector = match sectores.get(&actividad.sector) {
+                    Some(sector) => sector,
+                    None => return Err(From::from("El estado hace referencia a un sector que no existe"))
+                };
+
+                celda.add_activity(sector, actividad.size, actividad.growth_factor);
+            };
+
+            celdas.insert(estado_celda.cve.to_owned(), celda);
+        };
+
+        match self.metrica.clone() {
+            None => {},
+            Some(FuenteMetrica::Toro { ancho, alto }) => {
+                utilities::asignar_metrica(&mut celdas, Arc::new(Toro::new(ancho, alto)));
+            },
+            Some(FuenteMetrica::Red { nodos, aristas, dirigida, modo, velocidad_acceso }) => {
+                let red = redes::Red::leer(&nodos, &aristas, dirigida)?;
+                redes::asignar_red(&mut celdas, Arc::new(red), modo, velocidad_acceso)?;
+            },
+            Some(FuenteMetrica::Costos { ruta, ancho, faltantes }) => {
+                let matriz = costos::MatrizCostos::leer(&ruta, &celdas, ancho, faltantes)?;
+                costos::asignar_costos(&mut celdas, matriz);
+            },
+            Some(FuenteMetrica::Desconocida) => {
+                return Err(From::from("El estado usa una métrica de distancia que no se puede restaurar"))
+            }
+        };
+
+        Ok(celdas)
+    }
+
+    pub fn rng(&self) -> Option<ChaCha8Rng> {
+        self.rng.as_ref().map(|estado| {
+            let mut rng = ChaCha8Rng::from_seed(estado.semilla);
+            rng.set_stream(estado.stream);
+            rng.set_word_pos(estado.posicion);
+
+            rng
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn el_estado_se_recupera_tal_cual() {
+        let mut sectores = HashMap::new();
+        sectores.insert(String::from("s"), Sector::new("s", 1.5));
+
+        let mut celdas = HashMap::new();
+        let mut celda = Celda::new("a", 0.0, 0.0, 3.0);
+        celda.add_activity(&sectores["s"], 2.5, 0.1);
+        celdas.insert(String::from("a"), celda);
+        celdas.insert(String::from("b"), Celda::new("b", 1.0, 2.0, 4.0));
+
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        rng.next_u64();
+
+        let ruta = std::env::temp_dir().join("dyn_cen_estado_prueba.json");
+        let ruta = ruta.to_str().unwrap();
+
+        Estado::capturar(12, &sectores, &celdas, Some(&rng)).guardar(ruta).unwrap();
+        let estado = Estado::cargar(ruta).unwrap();
+        std::fs::remove_file(ruta).unwrap();
+
+        assert_eq!(estado.iteracion(), 12);
+
+        let sectores_leidos = estado.sectores();
+        let celdas_leidas = estado.celdas(&sectores_leidos).unwrap();
+
+        assert_eq!(celdas_leidas.len(), 2);
+        assert_eq!(celdas_leidas["b"].coordinates(), (1.0, 2.0));
+        assert_eq!(celdas_leidas["b"].population(), 4.0);
+        assert_eq!(celdas_leidas["a"].size_of_activity(&sectores_leidos["s"]).unwrap(), 2.5);
+        assert_eq!(estado.rng().unwrap().next_u64(), rng.next_u64());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::error::Error;
+use crate::centros::{Sector, Celda, Economy};
+
+pub struct Flujo {
+    pub origen: String,
+    pub destino: String,
+    pub flujo: f64,
+}
+
+pub struct MatrizFlujos {
+    origenes: Vec<String>,
+    destinos: Vec<String>,
+    valores: Vec<Vec<f64>>,
+}
+
+impl MatrizFlujos {
+    pub fn origenes(&self) -> &Vec<String> {
+        &self.origenes
+    }
+
+    pub fn destinos(&self) -> &Vec<String> {
+        &self.destinos
+    }
+
+    pub fn valor(&self, origen: usize, destino: usize) -> f64 {
+        self.valores[origen][destino]
+    }
+
+    pub fn flujos(&self) -> Vec<Flujo> {
+        let mut flujos = Vec::new();
+
+        for (i, origen) in self.origenes.iter().enumerate() {
+            for (j, destino) in self.destinos.iter().enumerate() {
+                flujos.push(Flujo {
+                    origen: origen.to_owned(),
+                    destino: destino.to_owned(),
+                    flujo: self.valores[i][j],
+                });
+            };
+        };
+
+        flujos
+    }
+
+    pub fn escribir(&self, ruta: &str) -> Result<(), Box<dyn Error>> {
+        escribir_flujos(&self.flujos(), ruta)
+    }
+}
+
+pub fn matriz_densa(celdas: &HashMap<String, Celda>, sector: &Sector) -> MatrizFlujos {
+    use rayon::prelude::*;
+
+    let mut origenes: Vec<String> = celdas.keys().cloned().collect();
+    origenes.sort();
+
+    let mut destinos: Vec<String> = celdas.iter().filter_map(|(cve, celda)| {
+        match celda.get_activity(sector) {
+            Some(_) => Some(cve.to_owned()),
+            None => None
+        }
+    }).collect();
+    destinos.sort();
+
+    let indices: HashMap<&String, usize> = destinos.iter().enumerate().map(|(j, cve)| (cve, j)).collect();
+
+    let valores: Vec<Vec<f64>> = origenes.par_iter().map(|origen| {
+        let mut fila = vec![0.0; destinos.len()];
+
+        for (destino, flujo) in celdas.consumer_flows(&celdas[origen], sector) {
+            fila[indices[&destino]] = flujo;
+        };
+
+        fila
+    }).collect();
+
+    MatrizFlujos {
+        origenes: origenes,
+        destinos: destinos,
+        valores: valores,
+    }
+}
+
+pub fn matriz_dispersa(celdas: &HashMap<String, Celda>, sector: &Sector, umbral: f64) -> Vec<Flujo> {
+    use rayon::prelude::*;
+
+    let mut origenes: Vec<&String> = celdas.keys().collect();
+    origenes.sort();
+
+    origenes.par_iter().flat_map_iter(|origen| {
+        let mut fila: Vec<Flujo> = celdas.consumer_flows(&celdas[*origen], sector).into_iter()
+            .filter(|(_, flujo)| *flujo > umbral)
+            .map(|(destino, flujo)| {
+                Flujo {
+                    origen: origen.to_string(),
+                    destino: destino,
+                    flujo: flujo,
+                }
+            }).collect();
+        fila.sort_by(|a, b| a.destino.cmp(&b.destino));
+
+        fila
+    }).collect()
+}
+
+pub fn escribir_flujos(flujos: &[Flujo], ruta: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(ruta)?;
+
+    wtr.write_record(["origen", "destino", "flujo"])?;
+
+    for flujo in flujos.iter() {
+        wtr.write_record([
+            &flujo.origen,
+            &flujo.destino,
+            &flujo.flujo.to_string(),
+        ])?;
+    };
+
+    wtr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use crate::centros::{Sector, Celda};
+use crate::agregados;
+
+pub struct Indices {
+    pub sobrevivientes: usize,
+    pub hhi: f64,
+    pub gini: f64,
+    pub entropia: f64,
+    pub primacia: f64,
+    pub participacion_top: f64,
+}
+
+fn participaciones(tamanos: &[f64]) -> Vec<f64> {
+    let total: f64 = tamanos.iter().sum();
+
+    tamanos.iter().map(|size| size / total).collect()
+}
+
+pub fn hhi(tamanos: &[f64]) -> f64 {
+    if tamanos.is_empty() {
+        return f64::NAN
+    }
+
+    participaciones(tamanos).iter().map(|s| s * s).sum()
+}
+
+pub fn gini(tamanos: &[f64]) -> f64 {
+    let mut ordenados = tamanos.to_vec();
+    ordenados.sort_by(|a, b| a.total_cmp(b));
+
+    let n = ordenados.len() as f64;
+    let total: f64 = ordenados.iter().sum();
+
+    if n == 0.0 || total == 0.0 {
+        return f64::NAN
+    }
+
+    let ponderada: f64 = ordenados.iter().enumerate().map(|(i, size)| (i + 1) as f64 * size).sum();
+
+    2.0 * ponderada / (n * total) - (n + 1.0) / n
+}
+
+pub fn entropia(tamanos: &[f64]) -> f64 {
+    if tamanos.is_empty() {
+        return f64::NAN
+    }
+
+    participaciones(tamanos).iter().filter(|s| **s > 0.0).map(|s| -s * s.ln()).sum()
+}
+
+pub fn primacia(tamanos: &[f64]) -> f64 {
+    let mut ordenados = tamanos.to_vec();
+    ordenados.sort_by(|a, b| b.total_cmp(a));
+
+    match ordenados.len() {
+        0 | 1 => f64::NAN,
+        _ => ordenados[0] / ordenados[1]
+    }
+}
+
+pub fn participacion_top(tamanos: &[f64], k: usize) -> f64 {
+    if tamanos.is_empty() {
+        return f64::NAN
+    }
+
+    let mut ordenados = participaciones(tamanos);
+    ordenados.sort_by(|a, b| b.total_cmp(a));
+
+    ordenados.iter().take(k).sum()
+}
+
+pub fn indices(tamanos: &[f64], umbral: f64, k: usize) -> Indices {
+    let vivos = agregados::vivos(tamanos, umbral);
+
+    Indices {
+        sobrevivientes: vivos.len(),
+        hhi: hhi(&vivos),
+        gini: gini(&vivos),
+        entropia: entropia(&vivos),
+        primacia: primacia(&vivos),
+        participacion_top: participacion_top(&vivos, k),
+    }
+}
+
+pub struct SalidaIndices {
+    umbral: f64,
+    k: usize,
+    writer: csv::Writer<File>,
+}
+
+impl SalidaIndices {
+    pub fn get_salida_indices(ruta: &str, umbral: f64, k: usize) -> Result<SalidaIndices, Box<dyn Error>> {
+
+        let mut writer = csv::Writer::from_path(ruta)?;
+
+        let top = format!("participacion_top_{}", k);
+        writer.write_record(["iteracion", "sector", "sobrevivientes", "hhi", "gini", "entropia", "primacia", &top])?;
+
+        Ok(SalidaIndices {
+            umbral: umbral,
+            k: k,
+            writer: writer,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) 
+        -> Result<(), Box<dyn Error>> {
+
+        let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+        cves_sectores.sort();
+
+        for cve_sector in cves_sectores {
+            let tamanos = agregados::tamanos_sector(celdas, &sectores[cve_sector]);
+            let indices = indices(&tamanos, self.umbral, self.k);
+
+            self.writer.write_record(&[
+                t.to_string(),
+                cve_sector.to_owned(),
+                indices.sobrevivientes.to_string(),
+                indices.hhi.to_string(),
+                indices.gini.to_string(),
+                indices.entropia.to_string(),
+                indices.primacia.to_string(),
+                indices.participacion_top.to_string(),
+            ])?;
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
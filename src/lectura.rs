@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use crate::centros::{Sector, Celda};
+
+pub struct RegistroCelda {
+    pub cve: String,
+    pub x: f64,
+    pub y: f64,
+    pub poblacion: f64,
+}
+
+pub struct Trayectoria {
+    pub sector: String,
+    pub centros: Vec<String>,
+    pub iteraciones: Vec<usize>,
+    pub tamanos: Vec<Vec<f64>>,
+}
+
+impl Trayectoria {
+    pub fn pasos(&self) -> usize {
+        self.tamanos.len()
+    }
+
+    pub fn serie(&self, centro: &str) -> Option<Vec<f64>> {
+        let j = self.centros.iter().position(|cve| cve == centro)?;
+
+        Some(self.tamanos.iter().map(|fila| fila[j]).collect())
+    }
+
+    pub fn paso(&self, iteracion: usize) -> Option<&Vec<f64>> {
+        let k = self.iteraciones.iter().position(|t| *t == iteracion)?;
+
+        self.tamanos.get(k)
+    }
+
+    pub fn ultimo(&self) -> Option<&Vec<f64>> {
+        self.tamanos.last()
+    }
+}
+
+pub fn normalizar_cve(cve: &str, ancho: usize) -> String {
+    let cve = cve.trim();
+
+    match cve.len() < ancho && !cve.is_empty() && cve.chars().all(|c| c.is_ascii_digit()) {
+        true => format!("{:0>ancho$}", cve, ancho = ancho),
+        false => cve.to_owned()
+    }
+}
+
+pub fn preparar_cve(cve: &str, ancho: Option<usize>) -> String {
+    match ancho {
+        Some(ancho) => normalizar_cve(cve, ancho),
+        None => cve.trim().to_owned()
+    }
+}
+
+pub fn leer_topologia(ruta: &str, ancho: Option<usize>) -> Result<Vec<RegistroCelda>, Box<dyn Error>> {
+
+    let mut rdr = csv::Reader::from_path(ruta)?;
+    let mut registros = Vec::new();
+
+    for result in rdr.records() {
+        let record = result?;
+
+        registros.push(RegistroCelda {
+            cve: preparar_cve(&record[0], ancho),
+            x: record[1].parse::<f64>()?,
+            y: record[2].parse::<f64>()?,
+            poblacion: record[3].parse::<f64>()?,
+        });
+    };
+
+    Ok(registros)
+}
+
+pub fn leer_trayectoria(ruta: &str, ancho: Option<usize>) -> Result<Trayectoria, Box<dyn Error>> {
+
+    let sector = match Path::new(ruta).file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) => stem.to_owned(),
+        None => return Err(From::from("No se puede obtener el sector de la ruta"))
+    };
+
+    let mut rdr = csv::Reader::from_path(ruta)?;
+
+    let encabezado = rdr.headers()?.clone();
+    let con_iteracion = encabezado.get(0) == Some("iteracion");
+    let inicio = if con_iteracion { 1 } else { 0 };
+
+    let centros: Vec<String> = encabezado.iter().skip(inicio).map(|cve| preparar_cve(cve, ancho)).collect();
+
+    let mut iteraciones = Vec::new();
+    let mut tamanos = Vec::new();
+
+    for (k, result) in rdr.records().enumerate() {
+        let record = result?;
+
+        let t = match con_iteracion {
+            true => record[0].parse::<usize>()?,
+            false => k
+        };
+
+        let fila = record.iter().skip(inicio).map(|valor| valor.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()?;
+
+        if fila.len() != centros.len() {
+            return Err(From::from("El registro no tiene el mismo número de centros que el encabezado"))
+        }
+
+        iteraciones.push(t);
+        tamanos.push(fila);
+    };
+
+    Ok(Trayectoria {
+        sector: sector,
+        centros: centros,
+        iteraciones: iteraciones,
+        tamanos: tamanos,
+    })
+}
+
+pub fn leer_trayectorias(directorio: &str, sectores: &[&str], ancho: Option<usize>) -> Result<HashMap<String, Trayectoria>, Box<dyn Error>> {
+
+    let mut trayectorias = HashMap::new();
+
+    for sector in sectores.iter() {
+        let ruta = Path::new(directorio).join(format!("{}.csv", sector));
+        let ruta = match ruta.to_str() {
+            Some(ruta) => ruta.to_owned(),
+            None => return Err(From::from("Ruta inválida"))
+        };
+
+        trayectorias.insert(sector.to_string(), leer_trayectoria(&ruta, ancho)?);
+    };
+
+    Ok(trayectorias)
+}
+
+pub fn celdas_de_registros<'a>(registros: &[RegistroCelda]) -> HashMap<String, Celda<'a>> {
+    registros.iter().map(|registro| {
+        (registro.cve.to_owned(), Celda::new(&registro.cve, registro.x, registro.y, registro.poblacion))
+    }).collect()
+}
+
+pub fn reconstruir<'a>(registros: &[RegistroCelda], trayectoria: &Trayectoria, iteracion: usize, sector: &'a Sector, growth_factor: f64) 
+    -> Result<HashMap<String, Celda<'a>>, Box<dyn Error>> {
+
+    let mut celdas = celdas_de_registros(registros);
+
+    let tamanos = match trayectoria.paso(iteracion) {
+        Some(tamanos) => tamanos,
+        None => return Err(From::from("La trayectoria no tiene esa iteración"))
+    };
+
+    for (cve, size) in trayectoria.centros.iter().zip(tamanos.iter()) {
+        match celdas.get_mut(cve) {
+            Some(celda) => celda.add_activity(sector, *size, growth_factor),
+            None => return Err(From::from("La trayectoria tiene un centro que no está en la topología"))
+        }
+    };
+
+    Ok(celdas)
+}
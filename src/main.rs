@@ -1,8 +1,32 @@
 mod centros;
 mod utilities;
-
+mod agregados;
+mod flujos;
+mod calendario;
+mod estado;
+mod lectura;
+mod manifiesto;
+mod zipf;
+mod indices;
+mod espacial;
+mod simulacion;
+mod barrido;
+mod bifurcacion;
+mod muestreo;
+mod sensibilidad;
+mod topologias;
+mod poblacion;
+mod canonicos;
+mod redes;
+mod costos;
+mod shapefile;
+mod proyecciones;
+
+use std::collections::HashMap;
+use std::env;
 use std::error::Error;
 use std::process;
+use std::sync::Arc;
 
 // use easytiming::Timing;
 // use std::io::Stdout;
@@ -11,7 +35,18 @@ use centros::Economy;
 
 fn main() {
 
-    if let Err(err) = random_ensamble_random_topology() {
+    let args: Vec<String> = env::args().collect();
+
+    let resultado = match args.get(1).map(|arg| arg.as_str()) {
+        Some("reanudar") => match args.get(2) {
+            Some(ruta) => reanudar_grid(ruta),
+            None => Err(From::from("Falta la ruta del estado a reanudar"))
+        },
+        Some(nombre) => canonico(nombre),
+        None => random_ensamble_random_topology()
+    };
+
+    if let Err(err) = resultado {
         println!("{}", err);
         process::exit(1);
     }
@@ -19,9 +54,50 @@ fn main() {
 
 fn agebs() -> Result<(), Box<dyn Error>> {
     const ITERACIONES: usize = 200;
+    const SALIDA_LARGA: bool = false;
+    const UMBRAL_FLUJOS: f64 = 1e-3;
+    const COSTOS: Option<&str> = None;
+    const FALTANTES: costos::Faltantes = costos::Faltantes::Rechazar;
+    const RED: Option<(&str, &str, bool)> = None;
+    const MODO_RED: redes::Modo = redes::Modo::Demanda;
+    const VELOCIDAD_ACCESO: f64 = 1.0;
+    const ZONA_UTM: Option<u32> = None;
+
+    let mut manifiesto = manifiesto::Manifiesto::new("agebs");
+    manifiesto.parametro("topologia", "./datos/procesados/agebs_cdmx_pob.csv")?;
+    manifiesto.parametro("centros", "./datos/procesados/rests_cdmx.csv")?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("growth_factor", 0.5)?;
+    manifiesto.parametro("umbral_flujos", UMBRAL_FLUJOS)?;
+    manifiesto.parametro("costos", COSTOS)?;
+    manifiesto.parametro("costos_faltantes", FALTANTES)?;
+    manifiesto.parametro("red", RED)?;
+    manifiesto.parametro("modo_red", MODO_RED)?;
+    manifiesto.parametro("velocidad_acceso", VELOCIDAD_ACCESO)?;
+    manifiesto.parametro("zona_utm", ZONA_UTM)?;
 
     let mut celdas = utilities::topo_from_file("./datos/procesados/agebs_cdmx_pob.csv")?;
 
+    if let Some(zona) = ZONA_UTM {
+        let destino = proyecciones::TransversaMercator::utm(zona, true);
+        proyecciones::transformar(&mut celdas, &proyecciones::Geograficas, &destino);
+    }
+
+    match (COSTOS, RED) {
+        (Some(_), Some(_)) => return Err(From::from("Solo puede usarse una matriz de costos o una red, no ambas")),
+        (Some(ruta), None) => {
+            let matriz = costos::MatrizCostos::leer(ruta, &celdas, None, FALTANTES)?;
+            manifiesto.resultado("pares_sin_costo", matriz.faltantes())?;
+            costos::asignar_costos(&mut celdas, matriz);
+        },
+        (None, Some((nodos, aristas, dirigida))) => {
+            let red = Arc::new(redes::Red::leer(nodos, aristas, dirigida)?);
+            manifiesto.resultado("nodos_red", red.nodos().len())?;
+            redes::asignar_red(&mut celdas, red, MODO_RED, VELOCIDAD_ACCESO)?;
+        },
+        (None, None) => ()
+    };
+
     let proto_sectores = vec![
             (String::from("rs_1p00"),1.0),
             (String::from("rs_1p50"),1.5),
@@ -34,12 +110,28 @@ fn agebs() -> Result<(), Box<dyn Error>> {
 
     for (_,sector) in sectores.iter() {
         utilities::centers_from_file("./datos/procesados/rests_cdmx.csv", &mut celdas, sector)?;
+        manifiesto.sector(sector);
     }
     
     utilities::escribir_topologia(&celdas, "./salida/celdas_agebs.csv")?;
+    manifiesto.archivo("./salida/celdas_agebs.csv");
+
+    let mut cal_trayectorias = calendario::Calendario::new(calendario::Frecuencia::Cada(1), ITERACIONES);
+    let mut cal_poblacion = calendario::Calendario::new(calendario::Frecuencia::Cada(10), ITERACIONES);
+    let mut cal_larga = calendario::Calendario::new(calendario::Frecuencia::CambioSobrevivientes(1e-3), ITERACIONES);
 
     let directorio = "./salida/";
-    let mut salida = utilities::get_salida(&sectores, &celdas, directorio)?;
+    let mut salida = utilities::get_salida(&sectores, &celdas, directorio, !cal_trayectorias.es_continuo())?;
+    let mut salida_indices = indices::SalidaIndices::get_salida_indices("./salida/indices_agebs.csv", 1e-3, 3)?;
+    let mut poblacion = utilities::SalidaPoblacion::get_salida_poblacion(&celdas, "./salida/poblacion_agebs.csv", false)?;
+    let mut salida_larga = match SALIDA_LARGA {
+        true => Some(utilities::SalidaLarga::get_salida_larga("./salida/agebs_larga.csv")?),
+        false => None
+    };
+
+    let mut convergencias: HashMap<String, agregados::Convergencia> = sectores.keys().map(|cve| {
+        (cve.to_owned(), agregados::Convergencia::new(1e-6))
+    }).collect();
 
     for t in 0..ITERACIONES {
         {
@@ -49,11 +141,113 @@ fn agebs() -> Result<(), Box<dyn Error>> {
         // }
 
         celdas.evolve(&sectores);
-        utilities::escribir_iteracion(&mut salida, &celdas)?;
+
+        for (cve, convergencia) in convergencias.iter_mut() {
+            convergencia.registrar(t, &celdas, &sectores[cve]);
+        }
+
+        if cal_trayectorias.toca(t, &sectores, &celdas) {
+            utilities::escribir_iteracion(&mut salida, &celdas, t)?;
+        }
+
+        if let Some(salida_larga) = salida_larga.as_mut() {
+            if cal_larga.toca(t, &sectores, &celdas) {
+                salida_larga.escribir_registro(t, &sectores, &celdas)?;
+            }
+        }
+
+        salida_indices.escribir_registro(t, &sectores, &celdas)?;
+
+        if cal_poblacion.toca(t, &sectores, &celdas) {
+            poblacion.escribir_registro(t, &celdas)?;
+        }
         }
     }
 
     utilities::flush_salida(&mut salida)?;
+    poblacion.flush_writer()?;
+    salida_indices.flush_writer()?;
+
+    for ruta in utilities::rutas_salida(&salida) {
+        manifiesto.archivo(&ruta);
+    }
+    manifiesto.archivo("./salida/indices_agebs.csv");
+    manifiesto.archivo("./salida/poblacion_agebs.csv");
+
+    if let Some(salida_larga) = salida_larga.as_mut() {
+        salida_larga.flush_writer()?;
+        manifiesto.archivo("./salida/agebs_larga.csv");
+    }
+
+    for (cve, convergencia) in convergencias.iter() {
+        manifiesto.convergencia(cve, convergencia.tiempo())?;
+    }
+
+    for (cve, sector) in sectores.iter() {
+        let flujos = flujos::matriz_dispersa(&celdas, sector, UMBRAL_FLUJOS);
+
+        let mut ruta = String::from(directorio);
+        ruta.push_str("flujos_");
+        ruta.push_str(cve);
+        ruta.push_str(".csv");
+
+        flujos::escribir_flujos(&flujos, &ruta)?;
+        manifiesto.archivo(&ruta);
+    }
+
+    manifiesto.escribir("./salida/manifiesto_agebs.json")?;
+
+    Ok(())
+}
+
+fn topologia_shp() -> Result<(), Box<dyn Error>> {
+
+    const CAPA: &str = "./datos/agebs_cdmx/09a.shp";
+    const POBLACION: &str = "./datos/procesados/agebs_cdmx_pob.csv";
+    const ZONA_UTM: Option<u32> = Some(14);
+
+    let mut manifiesto = manifiesto::Manifiesto::new("topologia_shp");
+    manifiesto.parametro("capa", CAPA)?;
+    manifiesto.parametro("poblacion", POBLACION)?;
+    manifiesto.parametro("zona_utm", ZONA_UTM)?;
+
+    let mut capa = shapefile::Capa::leer(CAPA)?;
+
+    if let Some(zona) = ZONA_UTM {
+        let origen = proyecciones::desde_prj(&CAPA.replace(".shp", ".prj"))?;
+        let destino = proyecciones::TransversaMercator::utm(zona, true);
+        capa.proyectar(origen.as_ref(), &destino);
+    }
+
+    capa.escribir_centroides("./salida/agebs_centroides.csv")?;
+    manifiesto.archivo("./salida/agebs_centroides.csv");
+
+    let mut topologia = capa.topologia("CVEGEO", None, 0.0)?;
+    topologia.escribir_atributos("./salida/atributos_agebs_shp.csv")?;
+    manifiesto.archivo("./salida/atributos_agebs_shp.csv");
+
+    let celdas = &mut topologia.celdas;
+
+    let poblaciones: HashMap<String, f64> = lectura::leer_topologia(POBLACION, Some(13))?.into_iter()
+        .map(|registro| (registro.cve, registro.poblacion))
+        .collect();
+
+    let mut sin_poblacion = 0;
+
+    for (cve, celda) in celdas.iter_mut() {
+        match poblaciones.get(cve) {
+            Some(poblacion) => celda.populate(*poblacion),
+            None => sin_poblacion += 1
+        }
+    };
+
+    manifiesto.resultado("celdas", celdas.len())?;
+    manifiesto.resultado("sin_poblacion", sin_poblacion)?;
+
+    utilities::escribir_topologia(celdas, "./salida/celdas_agebs_shp.csv")?;
+    manifiesto.archivo("./salida/celdas_agebs_shp.csv");
+
+    manifiesto.escribir("./salida/manifiesto_topologia_shp.json")?;
 
     Ok(())
 }
@@ -66,8 +260,26 @@ fn random_ensamble_random_topology() -> Result<(), Box<dyn Error>> {
     const CENTROS: usize = 20;
     const ITERACIONES: usize = 200;
     const INSTANCIAS: usize = 1000;
+    const SEMILLA: u64 = 1;
+
+    let directorio = "./salida/ensamble_random_random/";
+
+    let mut manifiesto = manifiesto::Manifiesto::new("random_ensamble_random_topology");
+    manifiesto.parametro("x_max", X_MAX)?;
+    manifiesto.parametro("y_max", Y_MAX)?;
+    manifiesto.parametro("poblacion", POBLACION)?;
+    manifiesto.parametro("centros", CENTROS)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("growth_factor", 0.5)?;
+    manifiesto.parametro("size_inicial", 1.0)?;
+    manifiesto.parametro("etas", (0..30).map(|ent| 0.1 + 0.1 * ent as f64).collect::<Vec<f64>>())?;
+    manifiesto.parametro("salida", directorio)?;
 
     for i in 0..INSTANCIAS {
+        let semilla = SEMILLA + i as u64;
+        let mut rng = utilities::generador(semilla);
+        manifiesto.semilla(semilla);
 
         let istr = (i + 1).to_string();
         let mut cadena = String::from("i_");
@@ -90,14 +302,17 @@ fn random_ensamble_random_topology() -> Result<(), Box<dyn Error>> {
             let proto_sector = vec![(salstr.to_owned(),eta.to_owned())];
 
             let sectores = utilities::sectors_from_vec(proto_sector);
-            let centros = utilities::random_vec_of_cves(CENTROS, &celdas);
+            let centros = utilities::random_vec_of_cves_with(CENTROS, &celdas, &mut rng);
 
             for (_, sector) in sectores.iter() {
                 let _ = utilities::centers_from_vec(&centros, 1.0, &mut celdas, &sector);
+
+                if i == 0 {
+                    manifiesto.sector(sector);
+                }
             }
 
-            let directorio = "./salida/ensamble_random_random/";
-            let mut salida = utilities::get_salida(&sectores, &celdas, directorio)?;
+            let mut salida = utilities::get_salida(&sectores, &celdas, directorio, false)?;
             
             for t in 0..ITERACIONES {
                 if t % 50 == 0 {
@@ -107,7 +322,7 @@ fn random_ensamble_random_topology() -> Result<(), Box<dyn Error>> {
                 // println!("i = {}, t = {}", i, t);
     
                 celdas.evolve(&sectores);
-                utilities::escribir_iteracion(&mut salida, &celdas)?;
+                utilities::escribir_iteracion(&mut salida, &celdas, t)?;
             }
 
             utilities::flush_salida(&mut salida)?;
@@ -115,6 +330,296 @@ fn random_ensamble_random_topology() -> Result<(), Box<dyn Error>> {
 
     }
 
+    manifiesto.archivo("./salida/ensamble_random/celdas.csv");
+    manifiesto.escribir("./salida/ensamble_random_random/manifiesto_trayectorias.json")?;
+
+    Ok(())
+}
+
+fn random_ensamble_agregado() -> Result<(), Box<dyn Error>> {
+
+    const X_MAX: usize = 50;
+    const Y_MAX: usize = 50;
+    const POBLACION: f64 = 1.0;
+    const CENTROS: usize = 20;
+    const ITERACIONES: usize = 200;
+    const INSTANCIAS: usize = 1000;
+    const UMBRAL: f64 = 1e-3;
+    const TOLERANCIA: f64 = 1e-6;
+    const TRAYECTORIAS: bool = false;
+    const SEMILLA: u64 = 1;
+
+    let directorio = "./salida/ensamble_random_random/";
+    let resumen = "./salida/ensamble_random_random/resumen.csv";
+
+    let mut manifiesto = manifiesto::Manifiesto::new("random_ensamble_agregado");
+    manifiesto.parametro("x_max", X_MAX)?;
+    manifiesto.parametro("y_max", Y_MAX)?;
+    manifiesto.parametro("poblacion", POBLACION)?;
+    manifiesto.parametro("centros", CENTROS)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("umbral", UMBRAL)?;
+    manifiesto.parametro("tolerancia", TOLERANCIA)?;
+    manifiesto.parametro("growth_factor", 0.5)?;
+    manifiesto.parametro("size_inicial", 1.0)?;
+    manifiesto.parametro("etas", (0..30).map(|ent| 0.1 + 0.1 * ent as f64).collect::<Vec<f64>>())?;
+
+    let mut agregado = agregados::AgregadoEnsamble::new(UMBRAL);
+    let mut no_convergidas = 0;
+
+    for i in 0..INSTANCIAS {
+        let semilla = SEMILLA + i as u64;
+        let mut rng = utilities::generador(semilla);
+        manifiesto.semilla(semilla);
+
+        let istr = (i + 1).to_string();
+        let mut cadena = String::from("i_");
+        cadena.push_str(&istr);
+        cadena.push_str("_e_");
+
+        let proto_sectores: Vec<(String,f64)> = (0..30).map(|ent| {
+            let eta = 0.1 + 0.1 * ent as f64;
+            let streta = format!("{:.1}", eta);
+            let mut cad = cadena.to_owned();
+            cad.push_str(&streta);
+            let salstr = cad.replace(".","p");
+            (salstr,eta)
+        }).collect();
+
+        for (salstr, eta) in proto_sectores.iter() {
+            let mut celdas = utilities::grid_of_cells(X_MAX, Y_MAX, POBLACION);
+
+            let proto_sector = vec![(salstr.to_owned(),eta.to_owned())];
+
+            let sectores = utilities::sectors_from_vec(proto_sector);
+            let centros = utilities::random_vec_of_cves_with(CENTROS, &celdas, &mut rng);
+
+            for (_, sector) in sectores.iter() {
+                let _ = utilities::centers_from_vec(&centros, 1.0, &mut celdas, sector);
+            }
+
+            let sector = sectores.get(salstr).expect("El sector no existe");
+
+            if i == 0 {
+                manifiesto.sector(sector);
+            }
+
+            let mut salida = match TRAYECTORIAS {
+                true => Some(utilities::get_salida(&sectores, &celdas, directorio, false)?),
+                false => None
+            };
+
+            let mut convergencia = agregados::Convergencia::new(TOLERANCIA);
+
+            for t in 0..ITERACIONES {
+                if t % 50 == 0 {
+                    println!("i = {}, t = {}", i, t)
+                }
+
+                celdas.evolve(&sectores);
+                convergencia.registrar(t, &celdas, sector);
+
+                if let Some(salida) = salida.as_mut() {
+                    utilities::escribir_iteracion(salida, &celdas, t)?;
+                }
+            }
+
+            if let Some(salida) = salida.as_mut() {
+                utilities::flush_salida(salida)?;
+
+                for ruta in utilities::rutas_salida(salida) {
+                    manifiesto.archivo(&ruta);
+                }
+            }
+
+            if convergencia.tiempo().is_none() {
+                no_convergidas += 1;
+            }
+
+            agregado.agregar_instancia(*eta, &celdas, sector, &convergencia);
+        }
+    }
+
+    agregado.escribir(resumen)?;
+    manifiesto.archivo(resumen);
+    manifiesto.convergencia("no_convergidas", no_convergidas)?;
+    manifiesto.escribir("./salida/ensamble_random_random/manifiesto.json")?;
+
+    Ok(())
+}
+
+fn barrido_eta() -> Result<(), Box<dyn Error>> {
+
+    const INSTANCIAS: usize = 50;
+    const REFINAMIENTOS: usize = 4;
+    const SEMILLA: u64 = 1;
+    const DENSIDAD: simulacion::Densidad = simulacion::Densidad::Uniforme;
+
+    let mut escenario = simulacion::Escenario::new(1.0);
+    escenario.densidad = DENSIDAD;
+    let etas: Vec<f64> = (0..30).map(|ent| 0.1 + 0.1 * ent as f64).collect();
+
+    let mut manifiesto = manifiesto::Manifiesto::new("barrido_eta");
+    manifiesto.parametro("escenario", &escenario)?;
+    manifiesto.parametro("etas", &etas)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("refinamientos", REFINAMIENTOS)?;
+    manifiesto.parametro("parametro_orden", "fraccion_sobrevivientes")?;
+    manifiesto.semilla(SEMILLA);
+
+    let barrido = barrido::barrido_eta(&escenario, &etas, INSTANCIAS, REFINAMIENTOS, SEMILLA, simulacion::fraccion_sobrevivientes)?;
+
+    match barrido.critico {
+        Some(critico) => println!("eta critica = {:.4} +/- {:.4}", critico, barrido.error_critico),
+        None => println!("El parámetro de orden no cambia más allá del ruido del ensamble")
+    };
+
+    barrido::escribir_barrido(&barrido, "./salida/barrido_eta.csv")?;
+    manifiesto.archivo("./salida/barrido_eta.csv");
+    manifiesto.resultado("eta_critica", barrido.critico)?;
+    manifiesto.resultado("error_eta_critica", barrido.error_critico)?;
+    manifiesto.resultado("corridas_invalidas", barrido.excluidos())?;
+    manifiesto.escribir("./salida/manifiesto_barrido_eta.json")?;
+
+    Ok(())
+}
+
+fn bifurcacion() -> Result<(), Box<dyn Error>> {
+
+    const INSTANCIAS: usize = 10;
+    const TRANSITORIO: usize = 150;
+    const TOLERANCIA: f64 = 1e-6;
+    const PERIODO_MAX: usize = 8;
+    const SEMILLA: u64 = 1;
+
+    let escenario = simulacion::Escenario::new(1.0);
+    let etas: Vec<f64> = (0..30).map(|ent| 0.1 + 0.1 * ent as f64).collect();
+
+    let mut manifiesto = manifiesto::Manifiesto::new("bifurcacion");
+    manifiesto.parametro("escenario", &escenario)?;
+    manifiesto.parametro("etas", &etas)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("transitorio", TRANSITORIO)?;
+    manifiesto.parametro("tolerancia", TOLERANCIA)?;
+    manifiesto.parametro("periodo_max", PERIODO_MAX)?;
+    manifiesto.semilla(SEMILLA);
+
+    let mut salida = bifurcacion::SalidaBifurcacion::get_salida_bifurcacion(
+        "./salida/bifurcacion.csv", "./salida/regimenes.csv", escenario.umbral, TOLERANCIA, PERIODO_MAX)?;
+
+    for eta in etas.iter() {
+        println!("eta = {:.1}", eta);
+        salida.escribir_eta(&escenario, *eta, INSTANCIAS, TRANSITORIO, SEMILLA)?;
+    }
+
+    salida.flush_writer()?;
+
+    manifiesto.archivo("./salida/bifurcacion.csv");
+    manifiesto.archivo("./salida/regimenes.csv");
+    manifiesto.escribir("./salida/manifiesto_bifurcacion.json")?;
+
+    Ok(())
+}
+
+fn barrido_parametros() -> Result<(), Box<dyn Error>> {
+
+    const PUNTOS: usize = 64;
+    const INSTANCIAS: usize = 10;
+    const SEMILLA: u64 = 1;
+
+    let escenario = simulacion::Escenario::new(1.0);
+
+    let rangos = vec![
+        muestreo::Rango::new("interaction", 0.1, 3.0),
+        muestreo::Rango::new("fixed_cost", 0.0, 2.0),
+        muestreo::Rango::new("cost_exp", 0.8, 1.2),
+        muestreo::Rango::new("p_capita_expenditure", 0.5, 2.0),
+        muestreo::Rango::new("growth_factor", 0.1, 1.0),
+        muestreo::Rango::new("centros", 5.0, 40.0),
+        muestreo::Rango::new("lado", 20.0, 60.0),
+    ];
+
+    let mut rng = utilities::generador(SEMILLA);
+    let puntos = muestreo::hipercubo_latino(&rangos, PUNTOS, &mut rng);
+
+    let mut manifiesto = manifiesto::Manifiesto::new("barrido_parametros");
+    manifiesto.parametro("escenario", &escenario)?;
+    manifiesto.parametro("muestreo", "hipercubo_latino")?;
+    manifiesto.parametro("rangos", rangos.iter().map(|rango| (&rango.nombre, rango.minimo, rango.maximo)).collect::<Vec<_>>())?;
+    manifiesto.parametro("puntos", PUNTOS)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.semilla(SEMILLA);
+
+    let resultados = barrido::barrido_parametros(&escenario, &puntos, INSTANCIAS, SEMILLA)?;
+
+    barrido::escribir_resultados(&resultados, "./salida/barrido_parametros.csv")?;
+    manifiesto.archivo("./salida/barrido_parametros.csv");
+    manifiesto.escribir("./salida/manifiesto_barrido_parametros.json")?;
+
+    Ok(())
+}
+
+fn sensibilidad() -> Result<(), Box<dyn Error>> {
+
+    const TRAYECTORIAS: usize = 20;
+    const NIVELES: usize = 4;
+    const MUESTRAS_SOBOL: usize = 128;
+    const INSTANCIAS: usize = 5;
+    const REPLICAS: usize = 500;
+    const NIVEL: f64 = 0.95;
+    const SEMILLA: u64 = 1;
+
+    let escenario = simulacion::Escenario::new(1.0);
+
+    let rangos = vec![
+        muestreo::Rango::new("interaction", 0.1, 3.0),
+        muestreo::Rango::new("pop_param", 0.0, 0.5),
+        muestreo::Rango::new("fixed_cost", 0.0, 2.0),
+        muestreo::Rango::new("returns_const", 0.5, 1.5),
+        muestreo::Rango::new("cost_exp", 0.8, 1.2),
+        muestreo::Rango::new("p_capita_expenditure", 0.5, 2.0),
+        muestreo::Rango::new("growth_factor", 0.1, 1.0),
+    ];
+    let metricas = ["sobrevivientes", "exponente_zipf", "participacion_max"];
+
+    let mut rng = utilities::generador(SEMILLA);
+
+    let mut manifiesto = manifiesto::Manifiesto::new("sensibilidad");
+    manifiesto.parametro("escenario", &escenario)?;
+    manifiesto.parametro("rangos", rangos.iter().map(|rango| (&rango.nombre, rango.minimo, rango.maximo)).collect::<Vec<_>>())?;
+    manifiesto.parametro("metricas", metricas)?;
+    manifiesto.parametro("trayectorias_morris", TRAYECTORIAS)?;
+    manifiesto.parametro("niveles_morris", NIVELES)?;
+    manifiesto.parametro("muestras_sobol", MUESTRAS_SOBOL)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("replicas_bootstrap", REPLICAS)?;
+    manifiesto.semilla(SEMILLA);
+
+    let diseno = sensibilidad::diseno_morris(&rangos, TRAYECTORIAS, NIVELES, &mut rng);
+    let resultados = barrido::barrido_parametros(&escenario, &diseno.puntos, INSTANCIAS, SEMILLA)?;
+    let indices = sensibilidad::morris(&diseno, &rangos, &resultados, &metricas, REPLICAS, NIVEL, &mut rng)?;
+    manifiesto.resultado("corridas_morris", resultados.len() * INSTANCIAS)?;
+    manifiesto.resultado("corridas_invalidas_morris", barrido::corridas_invalidas(&resultados))?;
+
+    barrido::escribir_resultados(&resultados, "./salida/morris_corridas.csv")?;
+    sensibilidad::escribir_indices(&indices, "./salida/morris.csv")?;
+    manifiesto.archivo("./salida/morris_corridas.csv");
+    manifiesto.archivo("./salida/morris.csv");
+
+    let diseno = sensibilidad::diseno_sobol(&rangos, MUESTRAS_SOBOL)?;
+    let resultados = barrido::barrido_parametros(&escenario, &diseno.puntos, INSTANCIAS, SEMILLA)?;
+    let indices = sensibilidad::sobol(&diseno, &rangos, &resultados, &metricas, REPLICAS, NIVEL, &mut rng)?;
+    manifiesto.resultado("corridas_sobol", resultados.len() * INSTANCIAS)?;
+    manifiesto.resultado("corridas_invalidas_sobol", barrido::corridas_invalidas(&resultados))?;
+
+    barrido::escribir_resultados(&resultados, "./salida/sobol_corridas.csv")?;
+    sensibilidad::escribir_indices(&indices, "./salida/sobol.csv")?;
+    manifiesto.archivo("./salida/sobol_corridas.csv");
+    manifiesto.archivo("./salida/sobol.csv");
+
+    manifiesto.escribir("./salida/manifiesto_sensibilidad.json")?;
+
     Ok(())
 }
 
@@ -126,8 +631,27 @@ fn random_ensamble_same_topology() -> Result<(), Box<dyn Error>> {
     const CENTROS: usize = 20;
     const ITERACIONES: usize = 200;
     const INSTANCIAS: usize = 100;
+    const SEMILLA: u64 = 1;
+
+    let directorio = "./salida/ensamble_random/";
+
+    let mut manifiesto = manifiesto::Manifiesto::new("random_ensamble_same_topology");
+    manifiesto.parametro("x_max", X_MAX)?;
+    manifiesto.parametro("y_max", Y_MAX)?;
+    manifiesto.parametro("poblacion", POBLACION)?;
+    manifiesto.parametro("centros", CENTROS)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("instancias", INSTANCIAS)?;
+    manifiesto.parametro("growth_factor", 0.5)?;
+    manifiesto.parametro("size_inicial", 1.0)?;
+    manifiesto.parametro("etas", (0..30).map(|ent| 0.1 + 0.1 * ent as f64).collect::<Vec<f64>>())?;
+    manifiesto.parametro("salida", directorio)?;
 
     for i in 0..INSTANCIAS {
+        let semilla = SEMILLA + i as u64;
+        let mut rng = utilities::generador(semilla);
+        manifiesto.semilla(semilla);
+
         let mut celdas = utilities::grid_of_cells(X_MAX, Y_MAX, POBLACION);
 
         let istr = (i + 1).to_string();
@@ -145,16 +669,24 @@ fn random_ensamble_same_topology() -> Result<(), Box<dyn Error>> {
         }).collect();
 
         let sectores = utilities::sectors_from_vec(proto_sectores);
-        let centros = utilities::random_vec_of_cves(CENTROS, &celdas);
+        let centros = utilities::random_vec_of_cves_with(CENTROS, &celdas, &mut rng);
 
         for (_, sector) in sectores.iter() {
             let _ = utilities::centers_from_vec(&centros, 1.0, &mut celdas, &sector);
         }
+
+        if i == 0 {
+            let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+            cves_sectores.sort();
+
+            for cve in cves_sectores {
+                manifiesto.sector(&sectores[cve]);
+            };
+        }
     
         utilities::escribir_topologia(&celdas, "./salida/ensamble_random/celdas.csv")?;
 
-        let directorio = "./salida/ensamble_random/";
-        let mut salida = utilities::get_salida(&sectores, &celdas, directorio)?;
+        let mut salida = utilities::get_salida(&sectores, &celdas, directorio, false)?;
 
         for t in 0..ITERACIONES {
             if t % 50 == 0 {
@@ -164,13 +696,16 @@ fn random_ensamble_same_topology() -> Result<(), Box<dyn Error>> {
             // println!("i = {}, t = {}", i, t);
 
             celdas.evolve(&sectores);
-            utilities::escribir_iteracion(&mut salida, &celdas)?;
+            utilities::escribir_iteracion(&mut salida, &celdas, t)?;
         }
 
         utilities::flush_salida(&mut salida)?;
 
     }
 
+    manifiesto.archivo("./salida/ensamble_random/celdas.csv");
+    manifiesto.escribir("./salida/ensamble_random/manifiesto.json")?;
+
     Ok(())
 
 }
@@ -183,19 +718,58 @@ fn random_grid() -> Result<(), Box<dyn Error>> {
     const POBLACION: f64 = 1.0;
     const CENTROS: usize = 20;
     const ITERACIONES: usize = 200;
-
-    let mut celdas = utilities::grid_of_cells(X_MAX, Y_MAX, POBLACION);
+    const SEMILLA: u64 = 1;
+    const ESTADO_CADA: usize = 50;
+    const PERIODICA: bool = false;
+
+    let mut manifiesto = manifiesto::Manifiesto::new("random_grid");
+    manifiesto.parametro("x_max", X_MAX)?;
+    manifiesto.parametro("y_max", Y_MAX)?;
+    manifiesto.parametro("poblacion", POBLACION)?;
+    manifiesto.parametro("centros", CENTROS)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("growth_factor", 0.5)?;
+    manifiesto.parametro("size_inicial", 1.0)?;
+    manifiesto.parametro("periodica", PERIODICA)?;
+    manifiesto.semilla(SEMILLA);
+
+    let mut celdas = match PERIODICA {
+        true => utilities::grid_of_cells_torus(X_MAX, Y_MAX, POBLACION),
+        false => utilities::grid_of_cells(X_MAX, Y_MAX, POBLACION)
+    };
+
+    let mut rng = utilities::generador(SEMILLA);
 
     let proto_sectores = vec![(String::from("sector_1"),1.0),(String::from("sector_2"),3.0)];
     let sectores = utilities::sectors_from_vec(proto_sectores);
-    for (_, sector) in sectores.iter() {
-        utilities::define_random_centers(CENTROS, &mut celdas, sector);
+    let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+    cves_sectores.sort();
+    for cve in cves_sectores {
+        utilities::define_random_centers_with(CENTROS, &mut celdas, &sectores[cve], &mut rng);
+        manifiesto.sector(&sectores[cve]);
     }
     
     utilities::escribir_topologia(&celdas, "./salida/celdas.csv")?;
+    manifiesto.archivo("./salida/celdas.csv");
+
+    let mut cal_trayectorias = calendario::Calendario::new(calendario::Frecuencia::Cada(1), ITERACIONES);
+    let mut cal_areas = calendario::Calendario::new(calendario::Frecuencia::Logaritmica(2.0), ITERACIONES);
+    let mut cal_poblacion = calendario::Calendario::new(calendario::Frecuencia::Cada(10), ITERACIONES);
+    let mut cal_zipf = calendario::Calendario::new(calendario::Frecuencia::Cada(10), ITERACIONES);
+    let mut cal_espacial = calendario::Calendario::new(calendario::Frecuencia::Final, ITERACIONES);
 
     let directorio = "./salida/";
-    let mut salida = utilities::get_salida(&sectores, &celdas, directorio)?;
+    let mut salida = utilities::get_salida(&sectores, &celdas, directorio, !cal_trayectorias.es_continuo())?;
+    let mut areas = utilities::SalidaAreas::get_salida_areas("./salida/areas.csv")?;
+    let mut salida_indices = indices::SalidaIndices::get_salida_indices("./salida/indices.csv", 1e-3, 3)?;
+    let mut poblacion = utilities::SalidaPoblacion::get_salida_poblacion(&celdas, "./salida/poblacion.csv", true)?;
+    let mut salida_zipf = zipf::SalidaZipf::get_salida_zipf("./salida/zipf.csv", Some("./salida/rango_tamano.csv"), 1e-3, 0.95)?;
+    let radios: Vec<f64> = (1..=25).map(|r| r as f64).collect();
+    let mut salida_espacial = espacial::SalidaEspacial::get_salida_espacial("./salida/espacial.csv", "./salida/ripley.csv", 1e-3, radios)?;
+
+    let mut convergencias: HashMap<String, agregados::Convergencia> = sectores.keys().map(|cve| {
+        (cve.to_owned(), agregados::Convergencia::new(1e-6))
+    }).collect();
 
     for t in 0..ITERACIONES {
         if t % 50 == 0 {
@@ -203,12 +777,180 @@ fn random_grid() -> Result<(), Box<dyn Error>> {
         }
 
         celdas.evolve(&sectores);
-        utilities::escribir_iteracion(&mut salida, &celdas)?;
+
+        for (cve, convergencia) in convergencias.iter_mut() {
+            convergencia.registrar(t, &celdas, &sectores[cve]);
+        }
+
+        if cal_trayectorias.toca(t, &sectores, &celdas) {
+            utilities::escribir_iteracion(&mut salida, &celdas, t)?;
+        }
+
+        if cal_areas.toca(t, &sectores, &celdas) {
+            areas.escribir_registro(t, &sectores, &celdas)?;
+        }
+
+        salida_indices.escribir_registro(t, &sectores, &celdas)?;
+
+        if cal_poblacion.toca(t, &sectores, &celdas) {
+            poblacion.escribir_registro(t, &celdas)?;
+        }
+
+        if cal_zipf.toca(t, &sectores, &celdas) {
+            salida_zipf.escribir_registro(t, &sectores, &celdas)?;
+        }
+
+        if cal_espacial.toca(t, &sectores, &celdas) {
+            salida_espacial.escribir_registro(t, &sectores, &celdas)?;
+        }
+
+        if (t + 1) % ESTADO_CADA == 0 {
+            let ruta = format!("./salida/estado_{}.json", t);
+            estado::Estado::capturar(t, &sectores, &celdas, Some(&rng)).guardar(&ruta)?;
+            manifiesto.archivo(&ruta);
+        }
+    }
+
+    utilities::flush_salida(&mut salida)?;
+    areas.flush_writer()?;
+    poblacion.flush_writer()?;
+    salida_indices.flush_writer()?;
+    salida_zipf.flush_writer()?;
+    salida_espacial.flush_writer()?;
+
+    for ruta in utilities::rutas_salida(&salida) {
+        manifiesto.archivo(&ruta);
+    }
+    manifiesto.archivo("./salida/indices.csv");
+    manifiesto.archivo("./salida/areas.csv");
+    manifiesto.archivo("./salida/poblacion.csv");
+    manifiesto.archivo("./salida/zipf.csv");
+    manifiesto.archivo("./salida/rango_tamano.csv");
+    manifiesto.archivo("./salida/espacial.csv");
+    manifiesto.archivo("./salida/ripley.csv");
+
+    for (cve, convergencia) in convergencias.iter() {
+        manifiesto.convergencia(cve, convergencia.tiempo())?;
+    }
+
+    for (cve, sector) in sectores.iter() {
+        let mut ruta = String::from(directorio);
+        ruta.push_str("flujos_");
+        ruta.push_str(cve);
+        ruta.push_str(".csv");
+
+        flujos::matriz_densa(&celdas, sector).escribir(&ruta)?;
+        manifiesto.archivo(&ruta);
+    }
+
+    manifiesto.escribir("./salida/manifiesto.json")?;
+
+    Ok(())
+}
+
+
+
+fn canonico(nombre: &str) -> Result<(), Box<dyn Error>> {
+
+    const ITERACIONES: usize = 200;
+    const ETA: f64 = 1.0;
+    const SIZE_INICIAL: f64 = 1.0;
+    const GROWTH_FACTOR: f64 = 0.5;
+
+    let canonico = canonicos::buscar(nombre)?;
+
+    let proto_sectores = vec![(String::from("sector_1"), ETA)];
+    let sectores = utilities::sectors_from_vec(proto_sectores);
+
+    let mut celdas = canonico.celdas(SIZE_INICIAL, GROWTH_FACTOR, &sectores["sector_1"])?;
+
+    let mut manifiesto = manifiesto::Manifiesto::new(&format!("canonico_{}", nombre));
+    manifiesto.parametro("escenario", canonico.nombre)?;
+    manifiesto.parametro("descripcion", canonico.descripcion)?;
+    manifiesto.parametro("celdas", canonico.n)?;
+    manifiesto.parametro("anillo", canonico.anillo)?;
+    manifiesto.parametro("centros", &canonico.centros)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+    manifiesto.parametro("size_inicial", SIZE_INICIAL)?;
+    manifiesto.parametro("growth_factor", GROWTH_FACTOR)?;
+    manifiesto.sector(&sectores["sector_1"]);
+
+    let directorio = format!("./salida/canonico_{}_", nombre);
+
+    let ruta = format!("{}celdas.csv", directorio);
+    utilities::escribir_topologia(&celdas, &ruta)?;
+    manifiesto.archivo(&ruta);
+
+    let mut salida = utilities::get_salida(&sectores, &celdas, &directorio, true)?;
+
+    for t in 0..ITERACIONES {
+        celdas.evolve(&sectores);
+        utilities::escribir_iteracion(&mut salida, &celdas, t)?;
     }
 
     utilities::flush_salida(&mut salida)?;
 
+    for ruta in utilities::rutas_salida(&salida) {
+        manifiesto.archivo(&ruta);
+    };
+
+    manifiesto.escribir(&format!("{}manifiesto.json", directorio))?;
+
     Ok(())
 }
 
+fn reanudar_grid(ruta: &str) -> Result<(), Box<dyn Error>> {
+
+    const ITERACIONES: usize = 200;
+    const ESTADO_CADA: usize = 50;
+
+    let estado = estado::Estado::cargar(ruta)?;
+
+    let sectores = estado.sectores();
+    // sectores.get_mut("sector_2").expect("El sector no existe").set_param("interaction", 2.0)?;
+
+    let mut celdas = estado.celdas(&sectores)?;
+    let rng = match estado.rng() {
+        Some(rng) => rng,
+        None => return Err(From::from("El estado no guarda el generador aleatorio"))
+    };
+
+    let mut manifiesto = manifiesto::Manifiesto::new("reanudar_grid");
+    manifiesto.parametro("estado", ruta)?;
+    manifiesto.parametro("iteracion_inicial", estado.iteracion() + 1)?;
+    manifiesto.parametro("iteraciones", ITERACIONES)?;
+
+    let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+    cves_sectores.sort();
+    for cve in cves_sectores {
+        manifiesto.sector(&sectores[cve]);
+    }
+
+    let directorio = "./salida/reanudado_";
+    let mut salida = utilities::get_salida(&sectores, &celdas, directorio, true)?;
+
+    for t in (estado.iteracion() + 1)..ITERACIONES {
+        if t % 50 == 0 {
+            println!("t = {}", t)
+        }
+
+        celdas.evolve(&sectores);
+        utilities::escribir_iteracion(&mut salida, &celdas, t)?;
+
+        if (t + 1) % ESTADO_CADA == 0 {
+            let ruta = format!("./salida/reanudado_estado_{}.json", t);
+            estado::Estado::capturar(t, &sectores, &celdas, Some(&rng)).guardar(&ruta)?;
+            manifiesto.archivo(&ruta);
+        }
+    }
+
+    utilities::flush_salida(&mut salida)?;
+
+    for ruta in utilities::rutas_salida(&salida) {
+        manifiesto.archivo(&ruta);
+    }
 
+    manifiesto.escribir("./salida/reanudado_manifiesto.json")?;
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+use serde_json::Value;
+use crate::centros::Sector;
+
+#[derive(Serialize)]
+pub struct Manifiesto {
+    corrida: String,
+    version: String,
+    inicio: u64,
+    fin: Option<u64>,
+    parametros: BTreeMap<String, Value>,
+    semillas: Vec<u64>,
+    sectores: Vec<Sector>,
+    #[serde(skip)]
+    cves_sectores: HashSet<String>,
+    convergencia: BTreeMap<String, Value>,
+    resultados: BTreeMap<String, Value>,
+    archivos: Vec<String>,
+    #[serde(skip)]
+    rutas: HashSet<String>,
+}
+
+fn ahora() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duracion) => duracion.as_secs(),
+        Err(_) => 0
+    }
+}
+
+impl Manifiesto {
+    pub fn new(corrida: &str) -> Self {
+        Manifiesto {
+            corrida: String::from(corrida),
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            inicio: ahora(),
+            fin: None,
+            parametros: BTreeMap::new(),
+            semillas: Vec::new(),
+            sectores: Vec::new(),
+            cves_sectores: HashSet::new(),
+            convergencia: BTreeMap::new(),
+            resultados: BTreeMap::new(),
+            archivos: Vec::new(),
+            rutas: HashSet::new(),
+        }
+    }
+
+    pub fn parametro<T: Serialize>(&mut self, nombre: &str, valor: T) -> Result<(), Box<dyn Error>> {
+        self.parametros.insert(String::from(nombre), serde_json::to_value(valor)?);
+
+        Ok(())
+    }
+
+    pub fn semilla(&mut self, semilla: u64) {
+        self.semillas.push(semilla);
+    }
+
+    pub fn sector(&mut self, sector: &Sector) {
+        if !self.cves_sectores.contains(sector.nombre()) {
+            self.cves_sectores.insert(sector.cve());
+            self.sectores.push(sector.clone());
+        }
+    }
+
+    pub fn convergencia<T: Serialize>(&mut self, clave: &str, valor: T) -> Result<(), Box<dyn Error>> {
+        self.convergencia.insert(String::from(clave), serde_json::to_value(valor)?);
+
+        Ok(())
+    }
+
+    pub fn resultado<T: Serialize>(&mut self, clave: &str, valor: T) -> Result<(), Box<dyn Error>> {
+        self.resultados.insert(String::from(clave), serde_json::to_value(valor)?);
+
+        Ok(())
+    }
+
+    pub fn archivo(&mut self, ruta: &str) {
+        if !self.rutas.contains(ruta) {
+            self.rutas.insert(String::from(ruta));
+            self.archivos.push(String::from(ruta));
+        }
+    }
+
+    pub fn escribir(&mut self, ruta: &str) -> Result<(), Box<dyn Error>> {
+        self.fin = Some(ahora());
+
+        let writer = BufWriter::new(File::create(ruta)?);
+        serde_json::to_writer_pretty(writer, self)?;
+
+        Ok(())
+    }
+}
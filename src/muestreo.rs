@@ -0,0 +1,165 @@
+use std::error::Error;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+pub struct Rango {
+    pub nombre: String,
+    pub minimo: f64,
+    pub maximo: f64,
+}
+
+impl Rango {
+    pub fn new(nombre: &str, minimo: f64, maximo: f64) -> Self {
+        Rango {
+            nombre: String::from(nombre),
+            minimo: minimo,
+            maximo: maximo,
+        }
+    }
+
+    pub fn escalar(&self, u: f64) -> f64 {
+        self.minimo + u * (self.maximo - self.minimo)
+    }
+}
+
+pub type Punto = Vec<(String, f64)>;
+
+pub fn factorial(niveles: &[(String, Vec<f64>)]) -> Vec<Punto> {
+
+    let mut puntos: Vec<Punto> = vec![Vec::new()];
+
+    for (nombre, valores) in niveles.iter() {
+        puntos = puntos.iter().flat_map(|punto| {
+            valores.iter().map(move |valor| {
+                let mut nuevo = punto.clone();
+                nuevo.push((nombre.to_owned(), *valor));
+                nuevo
+            })
+        }).collect();
+    };
+
+    puntos
+}
+
+pub fn hipercubo_latino<R: Rng>(rangos: &[Rango], n: usize, rng: &mut R) -> Vec<Punto> {
+
+    let columnas: Vec<Vec<f64>> = rangos.iter().map(|rango| {
+        let mut estratos: Vec<usize> = (0..n).collect();
+        estratos.shuffle(rng);
+
+        estratos.iter().map(|k| {
+            let u = (*k as f64 + rng.gen::<f64>()) / n as f64;
+            rango.escalar(u)
+        }).collect()
+    }).collect();
+
+    (0..n).map(|i| {
+        rangos.iter().zip(columnas.iter()).map(|(rango, columna)| (rango.nombre.to_owned(), columna[i])).collect()
+    }).collect()
+}
+
+const DIRECCIONES: [(u32, u32, [u32; 6]); 15] = [
+    (1, 0, [1, 0, 0, 0, 0, 0]),
+    (2, 1, [1, 3, 0, 0, 0, 0]),
+    (3, 1, [1, 3, 1, 0, 0, 0]),
+    (3, 2, [1, 1, 1, 0, 0, 0]),
+    (4, 1, [1, 1, 3, 3, 0, 0]),
+    (4, 4, [1, 3, 5, 13, 0, 0]),
+    (5, 2, [1, 1, 5, 5, 17, 0]),
+    (5, 4, [1, 1, 5, 5, 5, 0]),
+    (5, 7, [1, 1, 7, 11, 19, 0]),
+    (5, 11, [1, 1, 5, 1, 1, 0]),
+    (5, 13, [1, 1, 1, 3, 11, 0]),
+    (5, 14, [1, 3, 5, 5, 31, 0]),
+    (6, 1, [1, 3, 3, 9, 7, 49]),
+    (6, 13, [1, 1, 1, 15, 21, 21]),
+    (6, 16, [1, 3, 1, 13, 27, 49]),
+];
+
+const BITS: usize = 32;
+
+fn direcciones(dimension: usize) -> Vec<u32> {
+
+    let mut v = vec![0u32; BITS];
+
+    if dimension == 0 {
+        for i in 0..BITS {
+            v[i] = 1 << (BITS - 1 - i);
+        };
+
+        return v
+    }
+
+    let (s, a, m) = DIRECCIONES[dimension - 1];
+    let s = s as usize;
+
+    for i in 0..BITS {
+        if i < s {
+            v[i] = m[i] << (BITS - 1 - i);
+        } else {
+            let mut valor = v[i - s] ^ (v[i - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    valor ^= v[i - k];
+                }
+            };
+            v[i] = valor;
+        }
+    };
+
+    v
+}
+
+pub fn sobol_unitario(n: usize, dimensiones: usize) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+
+    if dimensiones > DIRECCIONES.len() + 1 {
+        return Err(From::from(format!("Sobol admite a lo más {} dimensiones y se pidieron {}", DIRECCIONES.len() + 1, dimensiones)))
+    }
+
+    if n as u64 >= 1u64 << BITS {
+        return Err(From::from("Sobol admite a lo más 2^32 - 1 puntos"))
+    }
+
+    let v: Vec<Vec<u32>> = (0..dimensiones).map(direcciones).collect();
+    let mut x = vec![0u32; dimensiones];
+    let escala = 2f64.powi(BITS as i32);
+
+    Ok((1..=n).map(|i| {
+        let c = (!(i - 1)).trailing_zeros() as usize;
+
+        for d in 0..dimensiones {
+            x[d] ^= v[d][c];
+        };
+
+        x.iter().map(|xd| *xd as f64 / escala).collect()
+    }).collect())
+}
+
+pub fn sobol(rangos: &[Rango], n: usize) -> Result<Vec<Punto>, Box<dyn Error>> {
+    Ok(sobol_unitario(n, rangos.len())?.into_iter().map(|u| {
+        rangos.iter().zip(u.iter()).map(|(rango, ui)| (rango.nombre.to_owned(), rango.escalar(*ui))).collect()
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primeros_puntos_de_sobol() {
+        let puntos = sobol_unitario(4, 2).unwrap();
+
+        assert_eq!(puntos, vec![
+            vec![0.5, 0.5],
+            vec![0.75, 0.25],
+            vec![0.25, 0.75],
+            vec![0.375, 0.375],
+        ]);
+    }
+
+    #[test]
+    fn sobol_rechaza_demasiadas_dimensiones() {
+        assert!(sobol_unitario(8, DIRECCIONES.len() + 1).is_ok());
+        assert!(sobol_unitario(8, DIRECCIONES.len() + 2).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use rand::Rng;
+use crate::centros::Celda;
+
+pub struct Nucleo {
+    pub centro: (f64, f64),
+    pub amplitud: f64,
+    pub sigma: f64,
+}
+
+fn distancia_a_punto(celda: &Celda, punto: (f64, f64)) -> f64 {
+    let mut referencia = Celda::new("", punto.0, punto.1, 0.0);
+
+    if let Some(metrica) = celda.metrica() {
+        referencia.set_metrica(metrica.clone());
+    }
+
+    referencia.distance(celda)
+}
+
+fn cves_ordenadas(celdas: &HashMap<String, Celda>) -> Vec<String> {
+    let mut cves: Vec<String> = celdas.keys().cloned().collect();
+    cves.sort();
+
+    cves
+}
+
+pub fn clark(celdas: &mut HashMap<String, Celda>, centro: (f64, f64), densidad_central: f64, gradiente: f64) {
+    for (_, celda) in celdas.iter_mut() {
+        let r = distancia_a_punto(celda, centro);
+        celda.populate(densidad_central * (-gradiente * r).exp());
+    };
+}
+
+pub fn mezcla_gaussiana(celdas: &mut HashMap<String, Celda>, nucleos: &[Nucleo], base: f64) {
+    for (_, celda) in celdas.iter_mut() {
+        let densidad: f64 = nucleos.iter().map(|nucleo| {
+            let r = distancia_a_punto(celda, nucleo.centro);
+            nucleo.amplitud * (-r * r / (2.0 * nucleo.sigma * nucleo.sigma)).exp()
+        }).sum();
+
+        celda.populate(base + densidad);
+    };
+}
+
+pub fn gradiente(celdas: &mut HashMap<String, Celda>, base: f64, pendiente: (f64, f64)) {
+    for (_, celda) in celdas.iter_mut() {
+        let (x, y) = celda.coordinates();
+        celda.populate((base + pendiente.0 * x + pendiente.1 * y).max(0.0));
+    };
+}
+
+pub fn lognormal<R: Rng>(celdas: &mut HashMap<String, Celda>, media: f64, sigma: f64, alcance: f64, rng: &mut R) {
+
+    let cves = cves_ordenadas(celdas);
+
+    let ruido: Vec<f64> = cves.iter().map(|_| {
+        let u: f64 = 1.0 - rng.gen::<f64>();
+        let v: f64 = rng.gen();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }).collect();
+
+    let campo: Vec<f64> = match alcance > 0.0 {
+        false => ruido,
+        true => cves.iter().map(|cve_i| {
+            let celda_i = &celdas[cve_i];
+
+            let (suma, cuadrados) = cves.iter().zip(ruido.iter()).fold((0.0, 0.0), |(suma, cuadrados), (cve_j, epsilon)| {
+                let d = celda_i.distance(&celdas[cve_j]);
+                let w = (-d * d / (2.0 * alcance * alcance)).exp();
+                (suma + w * epsilon, cuadrados + w * w)
+            });
+
+            suma / cuadrados.sqrt()
+        }).collect()
+    };
+
+    for (cve, z) in cves.iter().zip(campo.iter()) {
+        if let Some(celda) = celdas.get_mut(cve) {
+            celda.populate(media * (sigma * z - sigma * sigma / 2.0).exp());
+        }
+    };
+}
+
+pub fn normalizar(celdas: &mut HashMap<String, Celda>, total: f64) {
+
+    let actual: f64 = celdas.values().map(|celda| celda.population()).sum();
+
+    if actual <= 0.0 {
+        return
+    }
+
+    for (_, celda) in celdas.iter_mut() {
+        let poblacion = celda.population();
+        celda.populate(poblacion * total / actual);
+    };
+}
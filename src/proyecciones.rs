@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fs;
+use crate::centros::Celda;
+
+#[derive(Clone, Copy)]
+pub struct Elipsoide {
+    pub a: f64,
+    pub f: f64,
+}
+
+pub const GRS80: Elipsoide = Elipsoide {
+    a: 6378137.0,
+    f: 1.0 / 298.257222101,
+};
+
+pub const WGS84: Elipsoide = Elipsoide {
+    a: 6378137.0,
+    f: 1.0 / 298.257223563,
+};
+
+impl Elipsoide {
+    pub fn excentricidad(&self) -> f64 {
+        (self.f * (2.0 - self.f)).sqrt()
+    }
+}
+
+pub trait Proyeccion {
+    fn directa(&self, lon: f64, lat: f64) -> (f64, f64);
+
+    fn inversa(&self, x: f64, y: f64) -> (f64, f64);
+}
+
+pub struct Geograficas;
+
+impl Proyeccion for Geograficas {
+    fn directa(&self, lon: f64, lat: f64) -> (f64, f64) {
+        (lon, lat)
+    }
+
+    fn inversa(&self, x: f64, y: f64) -> (f64, f64) {
+        (x, y)
+    }
+}
+
+pub struct ConicaLambert {
+    elipsoide: Elipsoide,
+    lon0: f64,
+    falso_este: f64,
+    falso_norte: f64,
+    n: f64,
+    af: f64,
+    rho0: f64,
+}
+
+impl ConicaLambert {
+    pub fn new(elipsoide: Elipsoide, lon0: f64, lat0: f64, paralelo_1: f64, paralelo_2: f64, falso_este: f64, falso_norte: f64) -> Self {
+
+        let e = elipsoide.excentricidad();
+        let m = |phi: f64| phi.cos() / (1.0 - e * e * phi.sin() * phi.sin()).sqrt();
+        let t = |phi: f64| (PI / 4.0 - phi / 2.0).tan() / ((1.0 - e * phi.sin()) / (1.0 + e * phi.sin())).powf(e / 2.0);
+
+        let (phi1, phi2, phi0) = (paralelo_1.to_radians(), paralelo_2.to_radians(), lat0.to_radians());
+
+        let n = match (phi1 - phi2).abs() < 1e-12 {
+            true => phi1.sin(),
+            false => (m(phi1).ln() - m(phi2).ln()) / (t(phi1).ln() - t(phi2).ln())
+        };
+        let af = elipsoide.a * m(phi1) / (n * t(phi1).powf(n));
+
+        ConicaLambert {
+            elipsoide: elipsoide,
+            lon0: lon0.to_radians(),
+            falso_este: falso_este,
+            falso_norte: falso_norte,
+            n: n,
+            af: af,
+            rho0: af * t(phi0).powf(n),
+        }
+    }
+
+    pub fn inegi() -> Self {
+        ConicaLambert::new(GRS80, -102.0, 12.0, 17.5, 29.5, 2500000.0, 0.0)
+    }
+}
+
+impl Proyeccion for ConicaLambert {
+    fn directa(&self, lon: f64, lat: f64) -> (f64, f64) {
+
+        let e = self.elipsoide.excentricidad();
+        let phi = lat.to_radians();
+
+        let t = (PI / 4.0 - phi / 2.0).tan() / ((1.0 - e * phi.sin()) / (1.0 + e * phi.sin())).powf(e / 2.0);
+        let rho = self.af * t.powf(self.n);
+        let theta = self.n * (lon.to_radians() - self.lon0);
+
+        (self.falso_este + rho * theta.sin(), self.falso_norte + self.rho0 - rho * theta.cos())
+    }
+
+    fn inversa(&self, x: f64, y: f64) -> (f64, f64) {
+
+        let e = self.elipsoide.excentricidad();
+        let signo = self.n.signum();
+
+        let dx = x - self.falso_este;
+        let dy = self.rho0 - (y - self.falso_norte);
+
+        let rho = signo * (dx * dx + dy * dy).sqrt();
+        let t = (rho / self.af).powf(1.0 / self.n);
+        let theta = (signo * dx).atan2(signo * dy);
+
+        let mut phi = PI / 2.0 - 2.0 * t.atan();
+
+        for _ in 0..15 {
+            let siguiente = PI / 2.0 - 2.0 * (t * ((1.0 - e * phi.sin()) / (1.0 + e * phi.sin())).powf(e / 2.0)).atan();
+            let cambio = (siguiente - phi).abs();
+            phi = siguiente;
+
+            if cambio < 1e-14 {
+                break
+            }
+        };
+
+        ((theta / self.n + self.lon0).to_degrees(), phi.to_degrees())
+    }
+}
+
+pub struct TransversaMercator {
+    lon0: f64,
+    k0: f64,
+    falso_este: f64,
+    falso_norte: f64,
+    e: f64,
+    a_rect: f64,
+    alfa: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+    norte0: f64,
+}
+
+impl TransversaMercator {
+    pub fn new(elipsoide: Elipsoide, lon0: f64, lat0: f64, k0: f64, falso_este: f64, falso_norte: f64) -> Self {
+
+        let n = elipsoide.f / (2.0 - elipsoide.f);
+        let (n2, n3) = (n * n, n * n * n);
+
+        let mut proyeccion = TransversaMercator {
+            lon0: lon0.to_radians(),
+            k0: k0,
+            falso_este: falso_este,
+            falso_norte: falso_norte,
+            e: elipsoide.excentricidad(),
+            a_rect: elipsoide.a / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            alfa: [n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0, 13.0 * n2 / 48.0 - 3.0 * n3 / 5.0, 61.0 * n3 / 240.0],
+            beta: [n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0, n2 / 48.0 + n3 / 15.0, 17.0 * n3 / 480.0],
+            delta: [2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3, 7.0 * n2 / 3.0 - 8.0 * n3 / 5.0, 56.0 * n3 / 15.0],
+            norte0: 0.0,
+        };
+
+        proyeccion.norte0 = proyeccion.plana(lon0, lat0).1;
+
+        proyeccion
+    }
+
+    pub fn utm(zona: u32, norte: bool) -> Self {
+        let lon0 = -183.0 + 6.0 * zona as f64;
+        let falso_norte = match norte {
+            true => 0.0,
+            false => 10000000.0
+        };
+
+        TransversaMercator::new(GRS80, lon0, 0.0, 0.9996, 500000.0, falso_norte)
+    }
+
+    fn plana(&self, lon: f64, lat: f64) -> (f64, f64) {
+
+        let phi = lat.to_radians();
+        let lambda = lon.to_radians() - self.lon0;
+
+        let t = (phi.sin().atanh() - self.e * (self.e * phi.sin()).atanh()).sinh();
+        let xi = t.atan2(lambda.cos());
+        let eta = (lambda.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let mut este = eta;
+        let mut norte = xi;
+
+        for (j, alfa) in self.alfa.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            este += alfa * (k * xi).cos() * (k * eta).sinh();
+            norte += alfa * (k * xi).sin() * (k * eta).cosh();
+        };
+
+        (self.k0 * self.a_rect * este, self.k0 * self.a_rect * norte)
+    }
+}
+
+impl Proyeccion for TransversaMercator {
+    fn directa(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (este, norte) = self.plana(lon, lat);
+
+        (self.falso_este + este, self.falso_norte + norte - self.norte0)
+    }
+
+    fn inversa(&self, x: f64, y: f64) -> (f64, f64) {
+
+        let xi = (y - self.falso_norte + self.norte0) / (self.k0 * self.a_rect);
+        let eta = (x - self.falso_este) / (self.k0 * self.a_rect);
+
+        let mut xi_p = xi;
+        let mut eta_p = eta;
+
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_p -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_p -= beta * (k * xi).cos() * (k * eta).sinh();
+        };
+
+        let chi = (xi_p.sin() / eta_p.cosh()).asin();
+
+        let mut phi = chi;
+
+        for (j, delta) in self.delta.iter().enumerate() {
+            phi += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        };
+
+        let lambda = self.lon0 + eta_p.sinh().atan2(xi_p.cos());
+
+        (lambda.to_degrees(), phi.to_degrees())
+    }
+}
+
+pub struct Escalada {
+    proyeccion: Box<dyn Proyeccion>,
+    factor: f64,
+}
+
+impl Proyeccion for Escalada {
+    fn directa(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (x, y) = self.proyeccion.directa(lon, lat);
+
+        (x / self.factor, y / self.factor)
+    }
+
+    fn inversa(&self, x: f64, y: f64) -> (f64, f64) {
+        self.proyeccion.inversa(x * self.factor, y * self.factor)
+    }
+}
+
+fn escalar(proyeccion: Box<dyn Proyeccion>, factor: f64) -> Box<dyn Proyeccion> {
+    match (factor - 1.0).abs() < 1e-12 {
+        true => proyeccion,
+        false => Box::new(Escalada {
+            proyeccion: proyeccion,
+            factor: factor,
+        })
+    }
+}
+
+fn unidad_wkt(unidad: Option<&(String, Vec<String>)>, defecto: f64) -> Result<f64, Box<dyn Error>> {
+    let factor = match unidad {
+        Some((nombre, valores)) => match valores.first() {
+            Some(valor) => valor.parse::<f64>()?,
+            None => return Err(From::from(format!("La unidad {} no tiene factor de conversión", nombre)))
+        },
+        None => defecto
+    };
+
+    if !(factor > 0.0) || !factor.is_finite() {
+        return Err(From::from("El factor de conversión de la unidad debe ser positivo"))
+    }
+
+    Ok(factor)
+}
+
+fn parametros_wkt(wkt: &str, clave: &str) -> Vec<(String, Vec<String>)> {
+
+    let mut encontrados = Vec::new();
+    let patron = format!("{}[", clave);
+    let mut resto = wkt;
+
+    while let Some(inicio) = resto.find(&patron) {
+        let cuerpo = &resto[inicio + patron.len()..];
+        let fin = cuerpo.find(']').unwrap_or(cuerpo.len());
+
+        let mut partes = cuerpo[..fin].split(',').map(|parte| parte.trim().trim_matches('"').to_owned());
+        let nombre = partes.next().unwrap_or_default();
+        encontrados.push((nombre, partes.collect()));
+
+        resto = &cuerpo[fin..];
+    };
+
+    encontrados
+}
+
+pub fn desde_wkt(wkt: &str) -> Result<Box<dyn Proyeccion>, Box<dyn Error>> {
+
+    let elipsoide = match parametros_wkt(wkt, "SPHEROID").first() {
+        Some((_, valores)) if valores.len() >= 2 => {
+            let a = valores[0].parse::<f64>()?;
+            let inverso = valores[1].parse::<f64>()?;
+
+            Elipsoide {
+                a: a,
+                f: match inverso == 0.0 {
+                    true => 0.0,
+                    false => 1.0 / inverso
+                },
+            }
+        },
+        _ => return Err(From::from("La definición no tiene elipsoide"))
+    };
+
+    let unidades = parametros_wkt(wkt, "UNIT");
+    let grados = unidad_wkt(unidades.first(), 1f64.to_radians())? / 1f64.to_radians();
+
+    let proyeccion = match parametros_wkt(wkt, "PROJECTION").first() {
+        Some((nombre, _)) => nombre.to_owned(),
+        None => return Ok(escalar(Box::new(Geograficas), grados))
+    };
+
+    let metros = match unidades.len() > 1 {
+        true => unidad_wkt(unidades.last(), 1.0)?,
+        false => 1.0
+    };
+
+    let parametros: HashMap<String, f64> = parametros_wkt(wkt, "PARAMETER").into_iter().filter_map(|(nombre, valores)| {
+        valores.first().and_then(|valor| valor.parse::<f64>().ok()).map(|valor| (nombre.to_lowercase(), valor))
+    }).collect();
+
+    let parametro = |nombre: &str, defecto: Option<f64>| -> Result<f64, Box<dyn Error>> {
+        match parametros.get(nombre).cloned().or(defecto) {
+            Some(valor) => Ok(valor),
+            None => Err(From::from(format!("La definición no tiene el parámetro {}", nombre)))
+        }
+    };
+
+    let falso_este = parametro("false_easting", Some(0.0))? * metros;
+    let falso_norte = parametro("false_northing", Some(0.0))? * metros;
+    let lon0 = parametro("central_meridian", None)? * grados;
+    let lat0 = parametro("latitude_of_origin", Some(0.0))? * grados;
+
+    let plana: Box<dyn Proyeccion> = match proyeccion.as_str() {
+        "Lambert_Conformal_Conic" | "Lambert_Conformal_Conic_2SP" => {
+            let paralelo_1 = parametro("standard_parallel_1", None)? * grados;
+            let paralelo_2 = match parametros.get("standard_parallel_2") {
+                Some(paralelo) => paralelo * grados,
+                None => paralelo_1
+            };
+
+            Box::new(ConicaLambert::new(elipsoide, lon0, lat0, paralelo_1, paralelo_2, falso_este, falso_norte))
+        },
+        "Transverse_Mercator" => {
+            let k0 = parametro("scale_factor", Some(1.0))?;
+
+            Box::new(TransversaMercator::new(elipsoide, lon0, lat0, k0, falso_este, falso_norte))
+        },
+        _ => return Err(From::from(format!("Proyección no soportada: {}", proyeccion)))
+    };
+
+    Ok(escalar(plana, metros))
+}
+
+pub fn desde_prj(ruta: &str) -> Result<Box<dyn Proyeccion>, Box<dyn Error>> {
+    desde_wkt(&fs::read_to_string(ruta)?)
+}
+
+pub fn transformar(celdas: &mut HashMap<String, Celda>, origen: &dyn Proyeccion, destino: &dyn Proyeccion) {
+    for (_, celda) in celdas.iter_mut() {
+        let (x, y) = celda.coordinates();
+        let (lon, lat) = origen.inversa(x, y);
+        let (x, y) = destino.directa(lon, lat);
+
+        celda.set_coordinates(x, y);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ida_y_vuelta(proyeccion: &dyn Proyeccion, lon: f64, lat: f64, tolerancia: f64) {
+        let (x, y) = proyeccion.directa(lon, lat);
+        let (lon_vuelta, lat_vuelta) = proyeccion.inversa(x, y);
+
+        assert!((lon - lon_vuelta).abs() < tolerancia, "{} {}", lon, lon_vuelta);
+        assert!((lat - lat_vuelta).abs() < tolerancia, "{} {}", lat, lat_vuelta);
+    }
+
+    #[test]
+    fn conica_lambert_ida_y_vuelta() {
+        let lcc = ConicaLambert::inegi();
+
+        ida_y_vuelta(&lcc, -99.13, 19.43, 1e-9);
+        ida_y_vuelta(&lcc, -117.0, 32.5, 1e-9);
+        ida_y_vuelta(&lcc, -86.8, 21.1, 1e-9);
+
+        let (x, y) = lcc.directa(-102.0, 12.0);
+        assert!((x - 2500000.0).abs() < 1e-6 && y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn utm_ida_y_vuelta() {
+        let utm = TransversaMercator::utm(14, true);
+
+        // La serie de Krüger de tercer orden tiene errores de orden submilimétrico.
+        ida_y_vuelta(&utm, -99.13, 19.43, 1e-8);
+        ida_y_vuelta(&utm, -101.5, 25.0, 1e-8);
+
+        let (x, y) = utm.directa(-99.0, 0.0);
+        assert!((x - 500000.0).abs() < 1e-6 && y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn unidades_lineales_del_wkt() {
+        let metros = "PROJCS[\"m\",GEOGCS[\"g\",DATUM[\"d\",SPHEROID[\"GRS_1980\",6378137.0,298.257222101]],UNIT[\"Degree\",0.0174532925199433]],\
+            PROJECTION[\"Transverse_Mercator\"],PARAMETER[\"False_Easting\",500000.0],PARAMETER[\"Central_Meridian\",-99.0],\
+            PARAMETER[\"Scale_Factor\",0.9996],UNIT[\"Meter\",1.0]]";
+        let pies = metros.replace("500000.0", "1640419.9475065617").replace("UNIT[\"Meter\",1.0]", "UNIT[\"Foot\",0.3048]");
+
+        let (xm, ym) = desde_wkt(metros).unwrap().directa(-99.13, 19.43);
+        let (xp, yp) = desde_wkt(&pies).unwrap().directa(-99.13, 19.43);
+
+        assert!((xm - 0.3048 * xp).abs() < 1e-6);
+        assert!((ym - 0.3048 * yp).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,359 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::error::Error;
+use std::sync::{Arc, OnceLock};
+use serde::{Serialize, Deserialize};
+use crate::centros::{Celda, Metrica, Tabla, FuenteMetrica};
+use crate::utilities;
+
+pub struct Nodo {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+pub struct Red {
+    nodos: Vec<Nodo>,
+    indices: HashMap<String, usize>,
+    adyacencia: Vec<Vec<(usize, f64)>>,
+    ruta_nodos: String,
+    ruta_aristas: String,
+    dirigida: bool,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Modo {
+    Todos,
+    Demanda,
+}
+
+#[derive(PartialEq)]
+struct Pendiente {
+    costo: f64,
+    nodo: usize,
+}
+
+impl Eq for Pendiente {}
+
+impl Ord for Pendiente {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.costo.partial_cmp(&self.costo).unwrap_or(Ordering::Equal)
+            .then_with(|| self.nodo.cmp(&other.nodo))
+    }
+}
+
+impl PartialOrd for Pendiente {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Red {
+    pub fn leer(ruta_nodos: &str, ruta_aristas: &str, dirigida: bool) -> Result<Red, Box<dyn Error>> {
+
+        let mut nodos = Vec::new();
+        let mut indices = HashMap::new();
+
+        let mut rdr = csv::Reader::from_path(ruta_nodos)?;
+
+        for result in rdr.records() {
+            let record = result?;
+            let id = record[0].trim().to_owned();
+
+            if indices.contains_key(&id) {
+                return Err(From::from(format!("El nodo {} está repetido", id)))
+            }
+
+            indices.insert(id.to_owned(), nodos.len());
+            nodos.push(Nodo {
+                id: id,
+                x: record[1].parse::<f64>()?,
+                y: record[2].parse::<f64>()?,
+            });
+        };
+
+        let mut adyacencia = vec![Vec::new(); nodos.len()];
+
+        let mut rdr = csv::Reader::from_path(ruta_aristas)?;
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let origen = match indices.get(record[0].trim()) {
+                Some(i) => *i,
+                None => return Err(From::from(format!("La arista hace referencia al nodo inexistente {}", &record[0])))
+            };
+            let destino = match indices.get(record[1].trim()) {
+                Some(i) => *i,
+                None => return Err(From::from(format!("La arista hace referencia al nodo inexistente {}", &record[1])))
+            };
+            let costo = record[2].parse::<f64>()?;
+
+            if !(costo >= 0.0) {
+                return Err(From::from("Los costos de las aristas deben ser no negativos"))
+            }
+
+            adyacencia[origen].push((destino, costo));
+
+            if !dirigida {
+                adyacencia[destino].push((origen, costo));
+            }
+        };
+
+        Ok(Red {
+            nodos: nodos,
+            indices: indices,
+            adyacencia: adyacencia,
+            ruta_nodos: String::from(ruta_nodos),
+            ruta_aristas: String::from(ruta_aristas),
+            dirigida: dirigida,
+        })
+    }
+
+    pub fn nodos(&self) -> &Vec<Nodo> {
+        &self.nodos
+    }
+
+    pub fn indice(&self, id: &str) -> Option<usize> {
+        self.indices.get(id).cloned()
+    }
+
+    pub fn dijkstra(&self, origen: usize) -> Vec<f64> {
+
+        let mut costos = vec![f64::INFINITY; self.nodos.len()];
+        let mut cola = BinaryHeap::new();
+
+        costos[origen] = 0.0;
+        cola.push(Pendiente { costo: 0.0, nodo: origen });
+
+        while let Some(Pendiente { costo, nodo }) = cola.pop() {
+            if costo > costos[nodo] {
+                continue
+            }
+
+            for (vecino, longitud) in self.adyacencia[nodo].iter() {
+                let nuevo = costo + longitud;
+
+                if nuevo < costos[*vecino] {
+                    costos[*vecino] = nuevo;
+                    cola.push(Pendiente { costo: nuevo, nodo: *vecino });
+                }
+            };
+        };
+
+        costos
+    }
+
+    pub fn alcanzables(&self, origen: usize, inversa: bool) -> Vec<bool> {
+
+        let mut vecinos: Vec<Vec<usize>> = vec![Vec::new(); self.nodos.len()];
+
+        for (nodo, aristas) in self.adyacencia.iter().enumerate() {
+            for (destino, _) in aristas.iter() {
+                match inversa {
+                    true => vecinos[*destino].push(nodo),
+                    false => vecinos[nodo].push(*destino)
+                }
+            };
+        };
+
+        let mut visitados = vec![false; self.nodos.len()];
+        let mut pila = vec![origen];
+        visitados[origen] = true;
+
+        while let Some(nodo) = pila.pop() {
+            for vecino in vecinos[nodo].iter() {
+                if !visitados[*vecino] {
+                    visitados[*vecino] = true;
+                    pila.push(*vecino);
+                }
+            };
+        };
+
+        visitados
+    }
+
+    pub fn mas_cercano(&self, x: f64, y: f64) -> Option<(usize, f64)> {
+        self.nodos.iter().enumerate().map(|(i, nodo)| {
+            (i, ((nodo.x - x) * (nodo.x - x) + (nodo.y - y) * (nodo.y - y)).sqrt())
+        }).min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+    }
+}
+
+pub struct DistanciaRed {
+    red: Arc<Red>,
+    modo: Modo,
+    velocidad_acceso: f64,
+    indices: HashMap<String, usize>,
+    asignacion: Vec<(usize, f64)>,
+    coordenadas: Vec<(f64, f64)>,
+    tabla: Option<Tabla>,
+    filas: Vec<OnceLock<Vec<f64>>>,
+}
+
+impl DistanciaRed {
+    pub fn new(red: Arc<Red>, celdas: &HashMap<String, Celda>, modo: Modo, velocidad_acceso: f64) -> Result<DistanciaRed, Box<dyn Error>> {
+        use rayon::prelude::*;
+
+        if !(velocidad_acceso > 0.0) {
+            return Err(From::from("La velocidad de acceso a la red debe ser positiva"))
+        }
+
+        let mut cves: Vec<String> = celdas.keys().cloned().collect();
+        cves.sort();
+
+        let coordenadas: Vec<(f64, f64)> = cves.iter().map(|cve| celdas[cve].coordinates()).collect();
+
+        let asignacion: Vec<(usize, f64)> = coordenadas.par_iter().map(|(x, y)| {
+            red.mas_cercano(*x, *y).map(|(nodo, distancia)| (nodo, distancia / velocidad_acceso))
+        }).collect::<Option<Vec<(usize, f64)>>>()
+            .ok_or("La red no tiene nodos")?;
+
+        if let Some((primero, _)) = asignacion.first() {
+            let ida = red.alcanzables(*primero, false);
+            let vuelta = match red.dirigida {
+                true => red.alcanzables(*primero, true),
+                false => ida.clone()
+            };
+
+            if asignacion.iter().any(|(nodo, _)| !ida[*nodo] || !vuelta[*nodo]) {
+                return Err(From::from("Hay celdas que no pueden alcanzarse entre sí a través de la red"))
+            }
+        }
+
+        let mut metrica = DistanciaRed {
+            indices: cves.iter().enumerate().map(|(i, cve)| (cve.to_owned(), i)).collect(),
+            filas: (0..red.nodos.len()).map(|_| OnceLock::new()).collect(),
+            red: red,
+            modo: modo,
+            velocidad_acceso: velocidad_acceso,
+            asignacion: asignacion,
+            coordenadas: coordenadas,
+            tabla: None,
+        };
+
+        if let Modo::Todos = modo {
+            let n = cves.len();
+
+            let valores: Vec<f64> = (0..n).into_par_iter().flat_map_iter(|i| {
+                let metrica = &metrica;
+                (0..n).map(move |j| metrica.costo(i, j))
+            }).collect();
+
+            metrica.tabla = Some(Tabla::new(&cves, valores)?);
+            metrica.filas = (0..metrica.red.nodos.len()).map(|_| OnceLock::new()).collect();
+        }
+
+        Ok(metrica)
+    }
+
+    pub fn indices(&self) -> &HashMap<String, usize> {
+        &self.indices
+    }
+
+    pub fn asignacion(&self, cve: &str) -> Option<(usize, f64)> {
+        self.indices.get(cve).map(|i| self.asignacion[*i])
+    }
+
+    fn fila(&self, origen: usize) -> &Vec<f64> {
+        self.filas[origen].get_or_init(|| self.red.dijkstra(origen))
+    }
+
+    fn costo(&self, i: usize, j: usize) -> f64 {
+
+        if i == j {
+            return 0.0
+        }
+
+        let (nodo_origen, acceso_origen) = self.asignacion[i];
+        let (nodo_destino, acceso_destino) = self.asignacion[j];
+
+        if nodo_origen == nodo_destino {
+            let (xo, yo) = self.coordenadas[i];
+            let (xd, yd) = self.coordenadas[j];
+
+            return ((xo - xd) * (xo - xd) + (yo - yd) * (yo - yd)).sqrt() / self.velocidad_acceso
+        }
+
+        acceso_origen + self.fila(nodo_origen)[nodo_destino] + acceso_destino
+    }
+}
+
+impl Metrica for DistanciaRed {
+    fn fuente(&self) -> FuenteMetrica {
+        FuenteMetrica::Red {
+            nodos: self.red.ruta_nodos.to_owned(),
+            aristas: self.red.ruta_aristas.to_owned(),
+            dirigida: self.red.dirigida,
+            modo: self.modo,
+            velocidad_acceso: self.velocidad_acceso,
+        }
+    }
+
+    fn distancia(&self, origen: &Celda, destino: &Celda) -> f64 {
+
+        if let Some(tabla) = &self.tabla {
+            return tabla.distancia(origen, destino)
+        }
+
+        let i = origen.indice().or_else(|| self.indices.get(&origen.cve()).cloned());
+        let j = destino.indice().or_else(|| self.indices.get(&destino.cve()).cloned());
+
+        match (i, j) {
+            (Some(i), Some(j)) => self.costo(i, j),
+            _ => f64::INFINITY
+        }
+    }
+}
+
+pub fn asignar_red(celdas: &mut HashMap<String, Celda>, red: Arc<Red>, modo: Modo, velocidad_acceso: f64) -> Result<(), Box<dyn Error>> {
+
+    let metrica = Arc::new(DistanciaRed::new(red, celdas, modo, velocidad_acceso)?);
+    utilities::asignar_metrica_indexada(celdas, metrica.clone(), metrica.indices());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn red_de_prueba(nombre: &str, aristas: &str, dirigida: bool) -> Red {
+        let directorio = std::env::temp_dir();
+        let ruta_nodos = directorio.join(format!("dyn_cen_{}_nodos.csv", nombre));
+        let ruta_aristas = directorio.join(format!("dyn_cen_{}_aristas.csv", nombre));
+
+        std::fs::write(&ruta_nodos, "id,x,y\na,0,0\nb,1,0\nc,2,0\nd,3,0\n").unwrap();
+        std::fs::write(&ruta_aristas, aristas).unwrap();
+
+        let red = Red::leer(ruta_nodos.to_str().unwrap(), ruta_aristas.to_str().unwrap(), dirigida).unwrap();
+
+        std::fs::remove_file(ruta_nodos).unwrap();
+        std::fs::remove_file(ruta_aristas).unwrap();
+
+        red
+    }
+
+    #[test]
+    fn dijkstra_encuentra_el_camino_mas_barato() {
+        let red = red_de_prueba("dijkstra", "origen,destino,costo\na,b,1\nb,c,1\na,c,5\nc,d,2\n", false);
+
+        let origen = red.indice("a").unwrap();
+        let costos = red.dijkstra(origen);
+
+        assert_eq!(costos[red.indice("b").unwrap()], 1.0);
+        assert_eq!(costos[red.indice("c").unwrap()], 2.0);
+        assert_eq!(costos[red.indice("d").unwrap()], 4.0);
+    }
+
+    #[test]
+    fn dijkstra_respeta_el_sentido_de_las_aristas() {
+        let red = red_de_prueba("dirigida", "origen,destino,costo\na,b,1\nb,c,1\nc,d,1\n", true);
+
+        let costos = red.dijkstra(red.indice("d").unwrap());
+
+        assert_eq!(costos[red.indice("d").unwrap()], 0.0);
+        assert!(costos[red.indice("a").unwrap()].is_infinite());
+        assert!(!red.alcanzables(red.indice("a").unwrap(), true)[red.indice("d").unwrap()]);
+    }
+}
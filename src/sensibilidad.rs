@@ -0,0 +1,310 @@
+use std::error::Error;
+use rand::Rng;
+use rand::seq::SliceRandom;
+use crate::barrido::ResultadoPunto;
+use crate::muestreo::{self, Rango, Punto};
+
+pub struct DisenoMorris {
+    pub puntos: Vec<Punto>,
+    trayectorias: usize,
+    delta: f64,
+    orden: Vec<Vec<(usize, f64)>>,
+}
+
+pub struct DisenoSobol {
+    pub puntos: Vec<Punto>,
+    n: usize,
+}
+
+pub struct Indice {
+    pub parametro: String,
+    pub metrica: String,
+    pub tipo: String,
+    pub valor: f64,
+    pub inferior: f64,
+    pub superior: f64,
+    pub excluidos: usize,
+}
+
+fn punto_unitario(rangos: &[Rango], u: &[f64]) -> Punto {
+    rangos.iter().zip(u.iter()).map(|(rango, ui)| (rango.nombre.to_owned(), rango.escalar(*ui))).collect()
+}
+
+fn percentiles(mut valores: Vec<f64>, nivel: f64) -> (f64, f64) {
+    valores.retain(|valor| valor.is_finite());
+
+    if valores.is_empty() {
+        return (f64::NAN, f64::NAN)
+    }
+
+    valores.sort_by(|a, b| a.total_cmp(b));
+
+    let alfa = (1.0 - nivel) / 2.0;
+    let posicion = |p: f64| ((p * (valores.len() - 1) as f64).round() as usize).min(valores.len() - 1);
+
+    (valores[posicion(alfa)], valores[posicion(1.0 - alfa)])
+}
+
+fn media(valores: &[f64]) -> f64 {
+    valores.iter().sum::<f64>() / valores.len() as f64
+}
+
+pub fn diseno_morris<R: Rng>(rangos: &[Rango], trayectorias: usize, niveles: usize, rng: &mut R) -> DisenoMorris {
+
+    let k = rangos.len();
+    let niveles = niveles.max(2);
+    let delta = niveles as f64 / (2.0 * (niveles - 1) as f64);
+    let bases: Vec<f64> = (0..niveles).map(|l| l as f64 / (niveles - 1) as f64).collect();
+    let tolerancia = 1e-12;
+
+    let mut puntos = Vec::new();
+    let mut orden = Vec::new();
+
+    for _ in 0..trayectorias {
+        let mut u: Vec<f64> = (0..k).map(|_| *bases.choose(rng).unwrap()).collect();
+
+        let mut dimensiones: Vec<usize> = (0..k).collect();
+        dimensiones.shuffle(rng);
+
+        puntos.push(punto_unitario(rangos, &u));
+
+        let pasos: Vec<(usize, f64)> = dimensiones.into_iter().map(|d| {
+            let signo = match rng.gen::<bool>() {
+                true => 1.0,
+                false => -1.0
+            };
+
+            let signo = match u[d] + signo * delta > 1.0 + tolerancia || u[d] + signo * delta < -tolerancia {
+                true => -signo,
+                false => signo
+            };
+
+            u[d] = (u[d] + signo * delta).max(0.0).min(1.0);
+            puntos.push(punto_unitario(rangos, &u));
+
+            (d, signo)
+        }).collect();
+
+        orden.push(pasos);
+    };
+
+    DisenoMorris {
+        puntos: puntos,
+        trayectorias: trayectorias,
+        delta: delta,
+        orden: orden,
+    }
+}
+
+pub fn morris<R: Rng>(diseno: &DisenoMorris, rangos: &[Rango], resultados: &[ResultadoPunto], metricas: &[&str], 
+    replicas: usize, nivel: f64, rng: &mut R) -> Result<Vec<Indice>, Box<dyn Error>> {
+
+    let k = rangos.len();
+
+    if resultados.len() != diseno.trayectorias * (k + 1) {
+        return Err(From::from("Los resultados no corresponden al diseño de Morris"))
+    }
+
+    let mut indices = Vec::new();
+
+    for metrica in metricas.iter() {
+        let y: Vec<f64> = resultados.iter().map(|resultado| resultado.media(metrica)).collect();
+
+        let mut efectos: Vec<Vec<f64>> = vec![Vec::new(); k];
+
+        for (r, pasos) in diseno.orden.iter().enumerate() {
+            let inicio = r * (k + 1);
+
+            for (paso, (d, signo)) in pasos.iter().enumerate() {
+                let efecto = (y[inicio + paso + 1] - y[inicio + paso]) / (signo * diseno.delta);
+
+                if efecto.is_finite() {
+                    efectos[*d].push(efecto);
+                }
+            };
+        };
+
+        for (d, rango) in rangos.iter().enumerate() {
+            let ee = &efectos[d];
+            let excluidos = diseno.trayectorias - ee.len();
+
+            if ee.is_empty() {
+                indices.push(Indice {
+                    parametro: rango.nombre.to_owned(),
+                    metrica: metrica.to_string(),
+                    tipo: String::from("mu_estrella"),
+                    valor: f64::NAN,
+                    inferior: f64::NAN,
+                    superior: f64::NAN,
+                    excluidos: excluidos,
+                });
+                continue
+            }
+
+            let mu = media(ee);
+            let absolutos: Vec<f64> = ee.iter().map(|e| e.abs()).collect();
+            let mu_estrella = media(&absolutos);
+            let sigma = match ee.len() {
+                1 => f64::NAN,
+                n => (ee.iter().map(|e| (e - mu) * (e - mu)).sum::<f64>() / (n - 1) as f64).sqrt()
+            };
+
+            let remuestras: Vec<f64> = (0..replicas).map(|_| {
+                let muestra: Vec<f64> = (0..absolutos.len()).map(|_| absolutos[rng.gen_range(0..absolutos.len())]).collect();
+                media(&muestra)
+            }).collect();
+            let (inferior, superior) = percentiles(remuestras, nivel);
+
+            for (tipo, valor, inferior, superior) in [
+                ("mu", mu, f64::NAN, f64::NAN),
+                ("mu_estrella", mu_estrella, inferior, superior),
+                ("sigma", sigma, f64::NAN, f64::NAN),
+            ] {
+                indices.push(Indice {
+                    parametro: rango.nombre.to_owned(),
+                    metrica: metrica.to_string(),
+                    tipo: String::from(tipo),
+                    valor: valor,
+                    inferior: inferior,
+                    superior: superior,
+                    excluidos: excluidos,
+                });
+            };
+        };
+    };
+
+    Ok(indices)
+}
+
+pub fn diseno_sobol(rangos: &[Rango], n: usize) -> Result<DisenoSobol, Box<dyn Error>> {
+
+    let k = rangos.len();
+    let muestras = muestreo::sobol_unitario(n, 2 * k)?;
+
+    let a: Vec<Vec<f64>> = muestras.iter().map(|fila| fila[..k].to_vec()).collect();
+    let b: Vec<Vec<f64>> = muestras.iter().map(|fila| fila[k..].to_vec()).collect();
+
+    let mut puntos: Vec<Punto> = Vec::new();
+    puntos.extend(a.iter().map(|u| punto_unitario(rangos, u)));
+    puntos.extend(b.iter().map(|u| punto_unitario(rangos, u)));
+
+    for i in 0..k {
+        puntos.extend(a.iter().zip(b.iter()).map(|(ua, ub)| {
+            let mut u = ua.clone();
+            u[i] = ub[i];
+            punto_unitario(rangos, &u)
+        }));
+    };
+
+    Ok(DisenoSobol {
+        puntos: puntos,
+        n: n,
+    })
+}
+
+fn indices_sobol(filas: &[usize], fa: &[f64], fb: &[f64], fab: &[f64]) -> (f64, f64) {
+
+    let n = filas.len() as f64;
+
+    let todos: Vec<f64> = filas.iter().flat_map(|j| vec![fa[*j], fb[*j]]).collect();
+    let media_total = media(&todos);
+    let varianza = todos.iter().map(|y| (y - media_total) * (y - media_total)).sum::<f64>() / (todos.len() as f64 - 1.0);
+
+    let primero = filas.iter().map(|j| fb[*j] * (fab[*j] - fa[*j])).sum::<f64>() / n / varianza;
+    let total = filas.iter().map(|j| (fa[*j] - fab[*j]) * (fa[*j] - fab[*j])).sum::<f64>() / (2.0 * n) / varianza;
+
+    (primero, total)
+}
+
+pub fn sobol<R: Rng>(diseno: &DisenoSobol, rangos: &[Rango], resultados: &[ResultadoPunto], metricas: &[&str], 
+    replicas: usize, nivel: f64, rng: &mut R) -> Result<Vec<Indice>, Box<dyn Error>> {
+
+    let k = rangos.len();
+    let n = diseno.n;
+
+    if resultados.len() != n * (k + 2) {
+        return Err(From::from("Los resultados no corresponden al diseño de Sobol"))
+    }
+
+    let mut indices = Vec::new();
+
+    for metrica in metricas.iter() {
+        let y: Vec<f64> = resultados.iter().map(|resultado| resultado.media(metrica)).collect();
+        let fa = &y[..n];
+        let fb = &y[n..2 * n];
+
+        for (i, rango) in rangos.iter().enumerate() {
+            let fab = &y[(2 + i) * n..(3 + i) * n];
+
+            let filas: Vec<usize> = (0..n).filter(|j| {
+                fa[*j].is_finite() && fb[*j].is_finite() && fab[*j].is_finite()
+            }).collect();
+
+            let excluidos = n - filas.len();
+
+            if filas.len() < 2 {
+                for tipo in ["primer_orden", "total"].iter() {
+                    indices.push(Indice {
+                        parametro: rango.nombre.to_owned(),
+                        metrica: metrica.to_string(),
+                        tipo: tipo.to_string(),
+                        valor: f64::NAN,
+                        inferior: f64::NAN,
+                        superior: f64::NAN,
+                        excluidos: excluidos,
+                    });
+                };
+                continue
+            }
+
+            let (primero, total) = indices_sobol(&filas, fa, fb, fab);
+
+            let remuestras: Vec<(f64, f64)> = (0..replicas).map(|_| {
+                let muestra: Vec<usize> = (0..filas.len()).map(|_| filas[rng.gen_range(0..filas.len())]).collect();
+                indices_sobol(&muestra, fa, fb, fab)
+            }).collect();
+
+            let (inf_primero, sup_primero) = percentiles(remuestras.iter().map(|(s, _)| *s).collect(), nivel);
+            let (inf_total, sup_total) = percentiles(remuestras.iter().map(|(_, st)| *st).collect(), nivel);
+
+            for (tipo, valor, inferior, superior) in [
+                ("primer_orden", primero, inf_primero, sup_primero),
+                ("total", total, inf_total, sup_total),
+            ] {
+                indices.push(Indice {
+                    parametro: rango.nombre.to_owned(),
+                    metrica: metrica.to_string(),
+                    tipo: String::from(tipo),
+                    valor: valor,
+                    inferior: inferior,
+                    superior: superior,
+                    excluidos: excluidos,
+                });
+            };
+        };
+    };
+
+    Ok(indices)
+}
+
+pub fn escribir_indices(indices: &[Indice], ruta: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(ruta)?;
+
+    wtr.write_record(["parametro", "metrica", "tipo", "valor", "inferior", "superior", "excluidos"])?;
+
+    for indice in indices.iter() {
+        wtr.write_record([
+            &indice.parametro,
+            &indice.metrica,
+            &indice.tipo,
+            &indice.valor.to_string(),
+            &indice.inferior.to_string(),
+            &indice.superior.to_string(),
+            &indice.excluidos.to_string(),
+        ])?;
+    };
+
+    wtr.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,491 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use crate::centros::Celda;
+use crate::proyecciones::Proyeccion;
+
+pub struct Campo {
+    pub nombre: String,
+    pub tipo: char,
+    pub longitud: usize,
+}
+
+pub struct Poligono {
+    pub anillos: Vec<Vec<(f64, f64)>>,
+}
+
+pub struct Registro {
+    pub geometria: Option<Poligono>,
+    pub valores: Vec<String>,
+}
+
+pub struct Capa {
+    pub campos: Vec<Campo>,
+    pub registros: Vec<Registro>,
+}
+
+pub struct Atributos {
+    pub area: f64,
+    pub valores: Vec<String>,
+}
+
+pub struct Topologia<'a> {
+    pub celdas: HashMap<String, Celda<'a>>,
+    pub campos: Vec<String>,
+    pub atributos: HashMap<String, Atributos>,
+}
+
+fn be_i32(bytes: &[u8], pos: usize) -> Result<i32, Box<dyn Error>> {
+    match bytes.get(pos..pos + 4) {
+        Some(b) => Ok(i32::from_be_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(From::from("El archivo está truncado"))
+    }
+}
+
+fn le_i32(bytes: &[u8], pos: usize) -> Result<i32, Box<dyn Error>> {
+    match bytes.get(pos..pos + 4) {
+        Some(b) => Ok(i32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(From::from("El archivo está truncado"))
+    }
+}
+
+fn conteo(valor: i32) -> Result<usize, Box<dyn Error>> {
+    match valor >= 0 {
+        true => Ok(valor as usize),
+        false => Err(From::from(format!("El shapefile tiene un conteo o desplazamiento negativo: {}", valor)))
+    }
+}
+
+fn le_f64(bytes: &[u8], pos: usize) -> Result<f64, Box<dyn Error>> {
+    match bytes.get(pos..pos + 8) {
+        Some(b) => Ok(f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]])),
+        None => Err(From::from("El archivo está truncado"))
+    }
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+impl Poligono {
+    fn area_anillo(anillo: &[(f64, f64)]) -> (f64, f64, f64) {
+
+        let (x0, y0) = match anillo.first() {
+            Some(punto) => *punto,
+            None => return (0.0, 0.0, 0.0)
+        };
+
+        let mut area = 0.0;
+        let mut cx = 0.0;
+        let mut cy = 0.0;
+
+        for par in anillo.windows(2) {
+            let (xa, ya) = (par[0].0 - x0, par[0].1 - y0);
+            let (xb, yb) = (par[1].0 - x0, par[1].1 - y0);
+            let cruz = xa * yb - xb * ya;
+
+            area += cruz;
+            cx += (xa + xb) * cruz;
+            cy += (ya + yb) * cruz;
+        };
+
+        match area == 0.0 {
+            true => (0.0, x0, y0),
+            false => (area / 2.0, x0 + cx / (3.0 * area), y0 + cy / (3.0 * area))
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        self.anillos.iter().map(|anillo| Poligono::area_anillo(anillo).0).sum::<f64>().abs()
+    }
+
+    pub fn centroide(&self) -> (f64, f64) {
+
+        let (area, cx, cy) = self.anillos.iter().fold((0.0, 0.0, 0.0), |(area, cx, cy), anillo| {
+            let (a, x, y) = Poligono::area_anillo(anillo);
+            (area + a, cx + a * x, cy + a * y)
+        });
+
+        if area != 0.0 {
+            return (cx / area, cy / area)
+        }
+
+        let vertices: Vec<&(f64, f64)> = self.anillos.iter().flat_map(|anillo| anillo.iter()).collect();
+        let n = vertices.len() as f64;
+
+        (vertices.iter().map(|(x, _)| x).sum::<f64>() / n, vertices.iter().map(|(_, y)| y).sum::<f64>() / n)
+    }
+}
+
+fn leer_poligono(contenido: &[u8]) -> Result<Option<Poligono>, Box<dyn Error>> {
+
+    let tipo = le_i32(contenido, 0)?;
+
+    match tipo {
+        0 => return Ok(None),
+        5 | 15 | 25 => {},
+        _ => return Err(From::from(format!("Tipo de geometría no soportado: {}", tipo)))
+    };
+
+    let partes = conteo(le_i32(contenido, 36)?)?;
+    let puntos = conteo(le_i32(contenido, 40)?)?;
+
+    let inicio_partes = 44;
+    let inicio_puntos = inicio_partes + 4 * partes;
+
+    if inicio_puntos + 16 * puntos > contenido.len() {
+        return Err(From::from("El registro del polígono está truncado"))
+    }
+
+    let mut indices = Vec::with_capacity(partes + 1);
+    for i in 0..partes {
+        indices.push(conteo(le_i32(contenido, inicio_partes + 4 * i)?)?);
+    };
+    indices.push(puntos);
+
+    let mut anillos = Vec::with_capacity(partes);
+
+    for par in indices.windows(2) {
+        if par[0] > par[1] || par[1] > puntos {
+            return Err(From::from("Los índices de las partes del polígono no son válidos"))
+        }
+
+        let mut anillo = Vec::with_capacity(par[1] - par[0]);
+
+        for k in par[0]..par[1] {
+            let pos = inicio_puntos + 16 * k;
+            anillo.push((le_f64(contenido, pos)?, le_f64(contenido, pos + 8)?));
+        };
+
+        anillos.push(anillo);
+    };
+
+    Ok(Some(Poligono {
+        anillos: anillos,
+    }))
+}
+
+fn leer_geometrias(ruta_shp: &Path, ruta_shx: &Path) -> Result<Vec<Option<Poligono>>, Box<dyn Error>> {
+
+    let shp = fs::read(ruta_shp)?;
+    let shx = fs::read(ruta_shx)?;
+
+    if shp.len() < 100 || shx.len() < 100 {
+        return Err(From::from("El encabezado del shapefile está truncado"))
+    }
+
+    if be_i32(&shp, 0)? != 9994 || be_i32(&shx, 0)? != 9994 {
+        return Err(From::from("El archivo no es un shapefile"))
+    }
+
+    let registros = (shx.len() - 100) / 8;
+    let mut geometrias = Vec::with_capacity(registros);
+
+    for i in 0..registros {
+        let desplazamiento = 2 * conteo(be_i32(&shx, 100 + 8 * i)?)?;
+        let longitud = 2 * conteo(be_i32(&shx, 104 + 8 * i)?)?;
+
+        let contenido = match shp.get(desplazamiento + 8..desplazamiento + 8 + longitud) {
+            Some(contenido) => contenido,
+            None => return Err(From::from("El índice .shx apunta fuera del archivo .shp"))
+        };
+
+        geometrias.push(leer_poligono(contenido)?);
+    };
+
+    Ok(geometrias)
+}
+
+fn leer_dbf(ruta: &Path) -> Result<(Vec<Campo>, Vec<Option<Vec<String>>>), Box<dyn Error>> {
+
+    let dbf = fs::read(ruta)?;
+
+    if dbf.len() < 32 {
+        return Err(From::from("El archivo .dbf está truncado"))
+    }
+
+    let registros = u32::from_le_bytes([dbf[4], dbf[5], dbf[6], dbf[7]]) as usize;
+    let longitud_encabezado = u16::from_le_bytes([dbf[8], dbf[9]]) as usize;
+    let longitud_registro = u16::from_le_bytes([dbf[10], dbf[11]]) as usize;
+
+    if dbf.len() < longitud_encabezado {
+        return Err(From::from("El encabezado del .dbf está truncado"))
+    }
+
+    let mut campos = Vec::new();
+    let mut pos = 32;
+
+    while pos + 32 <= longitud_encabezado && dbf[pos] != 0x0d {
+        let nombre: Vec<u8> = dbf[pos..pos + 11].iter().cloned().take_while(|b| *b != 0).collect();
+
+        campos.push(Campo {
+            nombre: latin1(&nombre),
+            tipo: dbf[pos + 11] as char,
+            longitud: dbf[pos + 16] as usize,
+        });
+
+        pos += 32;
+    };
+
+    if 1 + campos.iter().map(|campo| campo.longitud).sum::<usize>() > longitud_registro {
+        return Err(From::from("Los campos del .dbf exceden la longitud del registro"))
+    }
+
+    let mut filas = Vec::with_capacity(registros);
+
+    for i in 0..registros {
+        let inicio = longitud_encabezado + i * longitud_registro;
+
+        let registro = match dbf.get(inicio..inicio + longitud_registro) {
+            Some(registro) => registro,
+            None => return Err(From::from("El archivo .dbf está truncado"))
+        };
+
+        if registro[0] == b'*' {
+            filas.push(None);
+            continue
+        }
+
+        let mut pos = 1;
+        let mut valores = Vec::with_capacity(campos.len());
+
+        for campo in campos.iter() {
+            valores.push(latin1(&registro[pos..pos + campo.longitud]).trim().to_owned());
+            pos += campo.longitud;
+        };
+
+        filas.push(Some(valores));
+    };
+
+    Ok((campos, filas))
+}
+
+impl Capa {
+    pub fn leer(ruta: &str) -> Result<Capa, Box<dyn Error>> {
+
+        let base = Path::new(ruta);
+
+        let geometrias = leer_geometrias(&base.with_extension("shp"), &base.with_extension("shx"))?;
+        let (campos, filas) = leer_dbf(&base.with_extension("dbf"))?;
+
+        if geometrias.len() != filas.len() {
+            return Err(From::from("El número de geometrías no coincide con el número de registros del .dbf"))
+        }
+
+        let registros = geometrias.into_iter().zip(filas.into_iter()).filter_map(|(geometria, valores)| {
+            valores.map(|valores| Registro {
+                geometria: geometria,
+                valores: valores,
+            })
+        }).collect();
+
+        Ok(Capa {
+            campos: campos,
+            registros: registros,
+        })
+    }
+
+    pub fn proyectar(&mut self, origen: &dyn Proyeccion, destino: &dyn Proyeccion) {
+        for registro in self.registros.iter_mut() {
+            if let Some(poligono) = registro.geometria.as_mut() {
+                for anillo in poligono.anillos.iter_mut() {
+                    for vertice in anillo.iter_mut() {
+                        let (lon, lat) = origen.inversa(vertice.0, vertice.1);
+                        *vertice = destino.directa(lon, lat);
+                    };
+                };
+            }
+        };
+    }
+
+    pub fn indice_campo(&self, nombre: &str) -> Result<usize, Box<dyn Error>> {
+        match self.campos.iter().position(|campo| campo.nombre == nombre) {
+            Some(indice) => Ok(indice),
+            None => Err(From::from(format!("La capa no tiene el campo {}", nombre)))
+        }
+    }
+
+    pub fn topologia<'a>(&self, campo_cve: &str, campo_poblacion: Option<&str>, poblacion: f64) -> Result<Topologia<'a>, Box<dyn Error>> {
+
+        let indice_cve = self.indice_campo(campo_cve)?;
+        let indice_poblacion = match campo_poblacion {
+            Some(campo) => Some(self.indice_campo(campo)?),
+            None => None
+        };
+
+        let mut celdas = HashMap::new();
+        let mut atributos = HashMap::new();
+
+        for registro in self.registros.iter() {
+            let poligono = match &registro.geometria {
+                Some(poligono) => poligono,
+                None => continue
+            };
+
+            let cve = registro.valores[indice_cve].to_owned();
+            let (x, y) = poligono.centroide();
+
+            if !x.is_finite() || !y.is_finite() {
+                return Err(From::from(format!("La geometría de {} no tiene vértices", cve)))
+            }
+
+            let pob = match indice_poblacion {
+                Some(indice) => registro.valores[indice].parse::<f64>()?,
+                None => poblacion
+            };
+
+            if celdas.insert(cve.to_owned(), Celda::new(&cve, x, y, pob)).is_some() {
+                return Err(From::from(format!("La clave {} está repetida en la capa", cve)))
+            }
+
+            atributos.insert(cve, Atributos {
+                area: poligono.area(),
+                valores: registro.valores.clone(),
+            });
+        };
+
+        Ok(Topologia {
+            celdas: celdas,
+            campos: self.campos.iter().map(|campo| campo.nombre.to_owned()).collect(),
+            atributos: atributos,
+        })
+    }
+
+    pub fn escribir_centroides(&self, ruta: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_path(ruta)?;
+
+        let mut encabezado = vec![String::from("x"), String::from("y"), String::from("area")];
+        encabezado.extend(self.campos.iter().map(|campo| campo.nombre.to_owned()));
+        wtr.write_record(&encabezado)?;
+
+        for registro in self.registros.iter() {
+            let (x, y, area) = match &registro.geometria {
+                Some(poligono) => {
+                    let (x, y) = poligono.centroide();
+                    (x, y, poligono.area())
+                },
+                None => (f64::NAN, f64::NAN, f64::NAN)
+            };
+
+            let mut fila = vec![x.to_string(), y.to_string(), area.to_string()];
+            fila.extend(registro.valores.iter().cloned());
+            wtr.write_record(&fila)?;
+        };
+
+        wtr.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<'a> Topologia<'a> {
+    pub fn escribir_atributos(&self, ruta: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_path(ruta)?;
+
+        let mut encabezado = vec![String::from("CVE"), String::from("area")];
+        encabezado.extend(self.campos.iter().cloned());
+        wtr.write_record(&encabezado)?;
+
+        let mut cves: Vec<&String> = self.atributos.keys().collect();
+        cves.sort();
+
+        for cve in cves {
+            let atributos = &self.atributos[cve];
+
+            let mut fila = vec![cve.to_owned(), atributos.area.to_string()];
+            fila.extend(atributos.valores.iter().cloned());
+            wtr.write_record(&fila)?;
+        };
+
+        wtr.flush()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registro(partes: i32, puntos: i32, indices: &[i32], vertices: &[(f64, f64)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&5i32.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 32]);
+        bytes.extend_from_slice(&partes.to_le_bytes());
+        bytes.extend_from_slice(&puntos.to_le_bytes());
+
+        for indice in indices.iter() {
+            bytes.extend_from_slice(&indice.to_le_bytes());
+        };
+
+        for (x, y) in vertices.iter() {
+            bytes.extend_from_slice(&x.to_le_bytes());
+            bytes.extend_from_slice(&y.to_le_bytes());
+        };
+
+        bytes
+    }
+
+    fn cuadrado() -> Vec<(f64, f64)> {
+        vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)]
+    }
+
+    #[test]
+    fn poligono_completo() {
+        let poligono = leer_poligono(&registro(1, 5, &[0], &cuadrado())).unwrap().unwrap();
+
+        assert_eq!(poligono.anillos.len(), 1);
+        assert!((poligono.area() - 4.0).abs() < 1e-12);
+        assert_eq!(poligono.centroide(), (1.0, 1.0));
+    }
+
+    #[test]
+    fn registros_truncados_o_negativos() {
+        let completo = registro(1, 5, &[0], &cuadrado());
+
+        assert!(leer_poligono(&completo[..completo.len() - 1]).is_err());
+        assert!(leer_poligono(&completo[..30]).is_err());
+        assert!(leer_poligono(&registro(1, 1_000_000, &[0], &cuadrado())).is_err());
+        assert!(leer_poligono(&registro(-1, 5, &[0], &cuadrado())).is_err());
+        assert!(leer_poligono(&registro(1, 5, &[-3], &cuadrado())).is_err());
+        assert!(leer_poligono(&registro(1, 5, &[6], &cuadrado())).is_err());
+    }
+
+    #[test]
+    fn centroide_degenerado_usa_la_media_de_los_vertices() {
+        let poligono = Poligono {
+            anillos: vec![vec![(0.0, 0.0), (2.0, 2.0), (4.0, 4.0)]],
+        };
+
+        assert_eq!(poligono.centroide(), (2.0, 2.0));
+    }
+
+    #[test]
+    fn indice_fuera_del_shapefile() {
+        let mut encabezado = vec![0u8; 100];
+        encabezado[..4].copy_from_slice(&9994i32.to_be_bytes());
+
+        let mut shx = encabezado.clone();
+        shx.extend_from_slice(&50i32.to_be_bytes());
+        shx.extend_from_slice(&1000i32.to_be_bytes());
+
+        let directorio = std::env::temp_dir();
+        let ruta_shp = directorio.join("dyn_cen_truncado.shp");
+        let ruta_shx = directorio.join("dyn_cen_truncado.shx");
+
+        fs::write(&ruta_shp, &encabezado).unwrap();
+        fs::write(&ruta_shx, &shx).unwrap();
+
+        let resultado = leer_geometrias(&ruta_shp, &ruta_shx);
+
+        fs::write(&ruta_shp, &encabezado[..50]).unwrap();
+        let encabezado_truncado = leer_geometrias(&ruta_shp, &ruta_shx);
+
+        fs::remove_file(ruta_shp).unwrap();
+        fs::remove_file(ruta_shx).unwrap();
+
+        assert!(resultado.is_err());
+        assert!(encabezado_truncado.is_err());
+    }
+}
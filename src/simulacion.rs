@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::error::Error;
+use rand::Rng;
+use serde::Serialize;
+use crate::centros::{Sector, Celda, Economy};
+use crate::utilities;
+use crate::agregados;
+use crate::poblacion;
+use crate::zipf;
+
+#[derive(Clone, Serialize)]
+pub enum Densidad {
+    Uniforme,
+    Clark { gradiente: f64 },
+    Gradiente { pendiente_x: f64, pendiente_y: f64 },
+    Gaussiana { nucleos: usize, sigma: f64, amplitud: f64 },
+    Lognormal { sigma: f64, alcance: f64 },
+}
+
+#[derive(Clone, Serialize)]
+pub struct Escenario {
+    pub x_max: usize,
+    pub y_max: usize,
+    pub poblacion: f64,
+    pub centros: usize,
+    pub iteraciones: usize,
+    pub size_inicial: f64,
+    pub growth_factor: f64,
+    pub umbral: f64,
+    pub tolerancia: f64,
+    pub periodica: bool,
+    pub densidad: Densidad,
+    pub sector: Sector,
+}
+
+pub struct Corrida {
+    pub valida: bool,
+    pub tamanos: Vec<f64>,
+    pub sobrevivientes: usize,
+    pub convergencia: Option<usize>,
+}
+
+impl Escenario {
+    pub fn new(eta: f64) -> Self {
+        Escenario {
+            x_max: 50,
+            y_max: 50,
+            poblacion: 1.0,
+            centros: 20,
+            iteraciones: 200,
+            size_inicial: 1.0,
+            growth_factor: 0.5,
+            umbral: 1e-3,
+            tolerancia: 1e-6,
+            periodica: false,
+            densidad: Densidad::Uniforme,
+            sector: Sector::new("escenario", eta),
+        }
+    }
+
+    pub fn con_eta(&self, eta: f64) -> Self {
+        let mut escenario = self.clone();
+        escenario.sector.set_param("interaction", eta).unwrap();
+
+        escenario
+    }
+
+    pub fn set_param(&mut self, nombre: &str, valor: f64) -> Result<(), Box<dyn Error>> {
+        match nombre {
+            "lado" => {
+                self.x_max = valor.round() as usize;
+                self.y_max = valor.round() as usize;
+            },
+            "x_max" => self.x_max = valor.round() as usize,
+            "y_max" => self.y_max = valor.round() as usize,
+            "poblacion" => self.poblacion = valor,
+            "centros" => self.centros = valor.round() as usize,
+            "iteraciones" => self.iteraciones = valor.round() as usize,
+            "size_inicial" => self.size_inicial = valor,
+            "growth_factor" => self.growth_factor = valor,
+            "periodica" => self.periodica = valor != 0.0,
+            _ => self.sector.set_param(nombre, valor)?
+        };
+
+        Ok(())
+    }
+
+    pub fn celdas<'a, R: Rng>(&self, rng: &mut R) -> HashMap<String, Celda<'a>> {
+        let mut celdas = match self.periodica {
+            true => utilities::grid_of_cells_torus(self.x_max, self.y_max, self.poblacion),
+            false => utilities::grid_of_cells(self.x_max, self.y_max, self.poblacion)
+        };
+
+        let total = self.poblacion * celdas.len() as f64;
+        let centro = ((self.x_max as f64 - 1.0) / 2.0, (self.y_max as f64 - 1.0) / 2.0);
+
+        match self.densidad {
+            Densidad::Uniforme => return celdas,
+            Densidad::Clark { gradiente } => poblacion::clark(&mut celdas, centro, 1.0, gradiente),
+            Densidad::Gradiente { pendiente_x, pendiente_y } => poblacion::gradiente(&mut celdas, 1.0, (pendiente_x, pendiente_y)),
+            Densidad::Gaussiana { nucleos, sigma, amplitud } => {
+                let nucleos: Vec<poblacion::Nucleo> = (0..nucleos).map(|_| {
+                    poblacion::Nucleo {
+                        centro: (rng.gen::<f64>() * self.x_max as f64, rng.gen::<f64>() * self.y_max as f64),
+                        amplitud: amplitud,
+                        sigma: sigma,
+                    }
+                }).collect();
+
+                poblacion::mezcla_gaussiana(&mut celdas, &nucleos, 1.0);
+            },
+            Densidad::Lognormal { sigma, alcance } => poblacion::lognormal(&mut celdas, 1.0, sigma, alcance, rng)
+        };
+
+        poblacion::normalizar(&mut celdas, total);
+
+        celdas
+    }
+
+    pub fn correr<R: Rng>(&self, rng: &mut R) -> Corrida {
+
+        let mut celdas = self.celdas(rng);
+
+        let centros = utilities::random_vec_of_cves_with(self.centros, &celdas, rng);
+        let _ = utilities::centers_from_vec_with_growth(&centros, self.size_inicial, self.growth_factor, &mut celdas, &self.sector);
+
+        let mut convergencia = agregados::Convergencia::new(self.tolerancia);
+
+        for t in 0..self.iteraciones {
+            celdas.update_sector_sizes(&self.sector);
+            celdas.update_populations();
+            convergencia.registrar(t, &celdas, &self.sector);
+        }
+
+        let tamanos = agregados::tamanos_sector(&celdas, &self.sector);
+        let valida = tamanos.iter().all(|size| size.is_finite());
+
+        Corrida {
+            valida: valida,
+            sobrevivientes: agregados::sobrevivientes(&tamanos, self.umbral),
+            convergencia: match valida {
+                true => convergencia.tiempo(),
+                false => None
+            },
+            tamanos: tamanos,
+        }
+    }
+
+    pub fn correr_historia<R: Rng>(&self, rng: &mut R, desde: usize) -> (Vec<String>, Vec<Vec<f64>>) {
+
+        let mut celdas = self.celdas(rng);
+
+        let mut centros = utilities::random_vec_of_cves_with(self.centros, &celdas, rng);
+        centros.sort();
+        let _ = utilities::centers_from_vec_with_growth(&centros, self.size_inicial, self.growth_factor, &mut celdas, &self.sector);
+
+        let mut historia = Vec::new();
+
+        for t in 0..self.iteraciones {
+            celdas.update_sector_sizes(&self.sector);
+            celdas.update_populations();
+
+            if t >= desde {
+                historia.push(centros.iter().map(|cve| {
+                    celdas[cve].size_of_activity(&self.sector).unwrap()
+                }).collect());
+            }
+        }
+
+        (centros, historia)
+    }
+}
+
+pub fn fraccion_sobrevivientes(escenario: &Escenario, corrida: &Corrida) -> f64 {
+    match corrida.valida {
+        true => corrida.sobrevivientes as f64 / escenario.centros as f64,
+        false => f64::NAN
+    }
+}
+
+pub fn metricas(escenario: &Escenario, corrida: &Corrida) -> Vec<(&'static str, f64)> {
+
+    if !corrida.valida {
+        return vec![
+            ("sobrevivientes", f64::NAN),
+            ("fraccion_sobrevivientes", f64::NAN),
+            ("participacion_max", f64::NAN),
+            ("exponente_zipf", f64::NAN),
+            ("convergencia", f64::NAN),
+        ]
+    }
+
+    let exponente = match zipf::ajuste_ols(&corrida.tamanos, escenario.umbral, 0.95) {
+        Some(ajuste) => ajuste.exponente,
+        None => f64::NAN
+    };
+
+    let convergencia = match corrida.convergencia {
+        Some(t) => t as f64,
+        None => f64::NAN
+    };
+
+    vec![
+        ("sobrevivientes", corrida.sobrevivientes as f64),
+        ("fraccion_sobrevivientes", fraccion_sobrevivientes(escenario, corrida)),
+        ("participacion_max", agregados::participacion_maxima(&corrida.tamanos, escenario.umbral)),
+        ("exponente_zipf", exponente),
+        ("convergencia", convergencia),
+    ]
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use rand::Rng;
+use rand_distr::{Distribution, Poisson};
+use crate::centros::{Celda, Toro};
+use crate::utilities;
+
+pub fn cve_de_indices(i: usize, j: usize) -> String {
+    format!("{:04}{:04}", i, j)
+}
+
+pub fn axial_a_cartesiano(q: i64, r: i64) -> (f64, f64) {
+    (q as f64 + r as f64 / 2.0, r as f64 * 3f64.sqrt() / 2.0)
+}
+
+pub fn hexagonal<'a>(columnas: usize, filas: usize, poblacion: f64, periodica: bool) -> Result<HashMap<String, Celda<'a>>, Box<dyn Error>> {
+
+    if periodica && !filas.is_multiple_of(2) {
+        return Err(From::from("Una malla hexagonal periódica necesita un número par de filas"))
+    }
+
+    let mut celdas: HashMap<String, Celda> = HashMap::new();
+
+    for fila in 0..filas {
+        for columna in 0..columnas {
+
+            let r = fila as i64;
+            let q = columna as i64 - r / 2;
+            let (x, y) = axial_a_cartesiano(q, r);
+
+            let cve = cve_de_indices(columna, fila);
+            let celda = Celda::new(&cve, x, y, poblacion);
+            celdas.insert(cve, celda);
+        }
+    };
+
+    if periodica {
+        let alto = filas as f64 * 3f64.sqrt() / 2.0;
+        utilities::asignar_metrica(&mut celdas, Arc::new(Toro::new(columnas as f64, alto)));
+    }
+
+    Ok(celdas)
+}
+
+pub fn cve_de_indice(i: usize) -> String {
+    format!("{:08}", i)
+}
+
+fn normal<R: Rng>(rng: &mut R) -> f64 {
+    let u: f64 = 1.0 - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+pub fn muestra_poisson<R: Rng>(media: f64, rng: &mut R) -> usize {
+    if !(media > 0.0) {
+        return 0
+    }
+
+    match Poisson::new(media) {
+        Ok(distribucion) => distribucion.sample(rng) as usize,
+        Err(_) => 0
+    }
+}
+
+fn celdas_de_puntos<'a>(puntos: &[(f64, f64)], poblacion: f64) -> HashMap<String, Celda<'a>> {
+    puntos.iter().enumerate().map(|(i, (x, y))| {
+        let cve = cve_de_indice(i);
+        (cve.to_owned(), Celda::new(&cve, *x, *y, poblacion))
+    }).collect()
+}
+
+pub fn uniforme<'a, R: Rng>(n: usize, ancho: f64, alto: f64, poblacion: f64, rng: &mut R) -> HashMap<String, Celda<'a>> {
+
+    let puntos: Vec<(f64, f64)> = (0..n).map(|_| {
+        (rng.gen::<f64>() * ancho, rng.gen::<f64>() * alto)
+    }).collect();
+
+    celdas_de_puntos(&puntos, poblacion)
+}
+
+pub fn poisson<'a, R: Rng>(intensidad: f64, ancho: f64, alto: f64, poblacion: f64, rng: &mut R) -> HashMap<String, Celda<'a>> {
+
+    let n = muestra_poisson(intensidad * ancho * alto, rng);
+
+    uniforme(n, ancho, alto, poblacion, rng)
+}
+
+fn agrupado<R: Rng, F>(intensidad_padres: f64, hijos_media: f64, margen: f64, ancho: f64, alto: f64, rng: &mut R, desplazamiento: F) -> Vec<(f64, f64)> 
+    where F: Fn(&mut R) -> (f64, f64) {
+
+    let ancho_ampliado = ancho + 2.0 * margen;
+    let alto_ampliado = alto + 2.0 * margen;
+    let padres = muestra_poisson(intensidad_padres * ancho_ampliado * alto_ampliado, rng);
+
+    let mut puntos = Vec::new();
+
+    for _ in 0..padres {
+        let px = rng.gen::<f64>() * ancho_ampliado - margen;
+        let py = rng.gen::<f64>() * alto_ampliado - margen;
+
+        for _ in 0..muestra_poisson(hijos_media, rng) {
+            let (dx, dy) = desplazamiento(rng);
+            let (x, y) = (px + dx, py + dy);
+
+            if x >= 0.0 && x < ancho && y >= 0.0 && y < alto {
+                puntos.push((x, y));
+            }
+        };
+    };
+
+    puntos
+}
+
+pub fn thomas<'a, R: Rng>(intensidad_padres: f64, hijos_media: f64, sigma: f64, ancho: f64, alto: f64, poblacion: f64, rng: &mut R) -> HashMap<String, Celda<'a>> {
+
+    let puntos = agrupado(intensidad_padres, hijos_media, 4.0 * sigma, ancho, alto, rng, |rng| {
+        (sigma * normal(rng), sigma * normal(rng))
+    });
+
+    celdas_de_puntos(&puntos, poblacion)
+}
+
+pub fn matern<'a, R: Rng>(intensidad_padres: f64, hijos_media: f64, radio: f64, ancho: f64, alto: f64, poblacion: f64, rng: &mut R) -> HashMap<String, Celda<'a>> {
+
+    let puntos = agrupado(intensidad_padres, hijos_media, radio, ancho, alto, rng, |rng| {
+        let r = radio * rng.gen::<f64>().sqrt();
+        let theta = 2.0 * std::f64::consts::PI * rng.gen::<f64>();
+        (r * theta.cos(), r * theta.sin())
+    });
+
+    celdas_de_puntos(&puntos, poblacion)
+}
+
+pub fn malla_perturbada<'a, R: Rng>(x_max: usize, y_max: usize, amplitud: f64, poblacion: f64, rng: &mut R) -> HashMap<String, Celda<'a>> {
+
+    let mut celdas: HashMap<String, Celda> = HashMap::new();
+
+    for x in 0..x_max {
+        for y in 0..y_max {
+            let dx = amplitud * (2.0 * rng.gen::<f64>() - 1.0);
+            let dy = amplitud * (2.0 * rng.gen::<f64>() - 1.0);
+
+            let cve = cve_de_indices(x, y);
+            let celda = Celda::new(&cve, x as f64 + dx, y as f64 + dy, poblacion);
+            celdas.insert(cve, celda);
+        }
+    };
+
+    celdas
+}
+
+pub fn linea<'a>(n: usize, espaciado: f64, poblacion: f64) -> HashMap<String, Celda<'a>> {
+
+    let mut celdas: HashMap<String, Celda> = HashMap::new();
+
+    for i in 0..n {
+        let cve = cve_de_indices(i, 0);
+        let celda = Celda::new(&cve, i as f64 * espaciado, 0.0, poblacion);
+        celdas.insert(cve, celda);
+    };
+
+    celdas
+}
+
+pub fn anillo<'a>(n: usize, espaciado: f64, poblacion: f64) -> HashMap<String, Celda<'a>> {
+
+    let mut celdas = linea(n, espaciado, poblacion);
+    utilities::asignar_metrica(&mut celdas, Arc::new(Toro::new(n as f64 * espaciado, 1.0)));
+
+    celdas
+}
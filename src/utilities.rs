@@ -1,7 +1,10 @@
 use std::collections::HashMap;
-use crate::centros::{Sector, Celda};
+use std::sync::Arc;
+use crate::centros::{Sector, Celda, Economy, Metrica, Toro};
 use std::fs::File;
 use std::error::Error;
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
 
 pub fn sectors_from_vec(sectores: Vec<(String, f64)>) -> HashMap<String, Sector> {
     let mut mapa = HashMap::new();
@@ -32,6 +35,29 @@ pub fn grid_of_cells<'a>(x_max: usize, y_max: usize, population: f64) -> HashMap
     celdas
 }
 
+pub fn grid_of_cells_torus<'a>(x_max: usize, y_max: usize, population: f64) -> HashMap<String, Celda<'a>> {
+
+    let mut celdas = grid_of_cells(x_max, y_max, population);
+    asignar_metrica(&mut celdas, Arc::new(Toro::new(x_max as f64, y_max as f64)));
+
+    celdas
+}
+
+pub fn asignar_metrica(celdas: &mut HashMap<String, Celda>, metrica: Arc<dyn Metrica>) {
+    for (_, celda) in celdas.iter_mut() {
+        celda.set_metrica(metrica.clone());
+    };
+}
+
+pub fn asignar_metrica_indexada(celdas: &mut HashMap<String, Celda>, metrica: Arc<dyn Metrica>, indices: &HashMap<String, usize>) {
+    for (cve, celda) in celdas.iter_mut() {
+        match indices.get(cve) {
+            Some(indice) => celda.set_metrica_indexada(metrica.clone(), *indice),
+            None => celda.set_metrica(metrica.clone())
+        }
+    };
+}
+
 pub fn topo_from_file<'a>(path: &str) -> Result<HashMap<String, Celda<'a>>,Box<dyn Error>> {
 
     let mut celdas: HashMap<String, Celda> = HashMap::new();
@@ -99,20 +125,31 @@ pub fn centers_from_file<'a>(path: &str, celdas: &mut HashMap<String,Celda<'a>>,
 
 }
 
+pub fn generador(semilla: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(semilla)
+}
+
 pub fn random_vec_of_cves<'a>(centros: usize, celdas: &HashMap<String,Celda<'a>>) -> Vec<String> {
-    use rand::prelude::*;
+    random_vec_of_cves_with(centros, celdas, &mut rand::thread_rng())
+}
+
+pub fn random_vec_of_cves_with<'a, R: Rng>(centros: usize, celdas: &HashMap<String,Celda<'a>>, rng: &mut R) -> Vec<String> {
 
-    let mut rng = &mut rand::thread_rng();
+    let mut cves: Vec<String> = celdas.keys().cloned().collect();
+    cves.sort();
 
-    let centers = centros.min(celdas.len());
-    let cves = celdas.iter().map(|(cve,_)| cve.to_owned()).choose_multiple(&mut rng, centers);
+    let centers = centros.min(cves.len());
+    let cves = cves.into_iter().choose_multiple(rng, centers);
 
     cves
 }
 
 pub fn centers_from_vec<'a>(cves: &Vec<String>, size: f64, celdas: &mut HashMap<String,Celda<'a>>, sector: &'a Sector) -> Result<Vec<String>,Box<dyn Error>> {
+    centers_from_vec_with_growth(cves, size, 0.5, celdas, sector)
+}
 
-    let growth_factor = 0.5;
+pub fn centers_from_vec_with_growth<'a>(cves: &Vec<String>, size: f64, growth_factor: f64, celdas: &mut HashMap<String,Celda<'a>>, sector: &'a Sector) 
+    -> Result<Vec<String>,Box<dyn Error>> {
 
     for cve in cves.iter() {
 
@@ -130,12 +167,12 @@ pub fn centers_from_vec<'a>(cves: &Vec<String>, size: f64, celdas: &mut HashMap<
 }
 
 pub fn define_random_centers<'a>(centros: usize, celdas: &mut HashMap<String,Celda<'a>>, sector: &'a Sector) -> Vec<String> {
-    use rand::prelude::*;
+    define_random_centers_with(centros, celdas, sector, &mut rand::thread_rng())
+}
 
-    let mut rng = &mut rand::thread_rng();
+pub fn define_random_centers_with<'a, R: Rng>(centros: usize, celdas: &mut HashMap<String,Celda<'a>>, sector: &'a Sector, rng: &mut R) -> Vec<String> {
 
-    let centers = centros.min(celdas.len());
-    let cves = celdas.iter().map(|(cve,_)| cve.to_owned()).choose_multiple(&mut rng, centers);
+    let cves = random_vec_of_cves_with(centros, celdas, rng);
 
     let initial_size = 1.0;
     let growth_factor = 0.5;
@@ -151,11 +188,13 @@ pub fn define_random_centers<'a>(centros: usize, celdas: &mut HashMap<String,Cel
 pub struct SalidaSector<'a> {
     sector: &'a Sector,
     centros: Vec<String>,
+    iteracion: bool,
+    ruta: String,
     writer: csv::Writer<File>,
 }
 
 impl<'a> SalidaSector<'a> {
-    pub fn get_salida_sector(sector: &'a Sector, celdas: &HashMap<String, Celda>, ruta: &str) -> Result<SalidaSector<'a>,Box<dyn Error>> {
+    pub fn get_salida_sector(sector: &'a Sector, celdas: &HashMap<String, Celda>, ruta: &str, iteracion: bool) -> Result<SalidaSector<'a>,Box<dyn Error>> {
 
         let cves: Vec<String> = celdas.iter().filter_map(|(cve, celda)| {
             match celda.get_activity(&sector) {
@@ -169,19 +208,32 @@ impl<'a> SalidaSector<'a> {
         let mut salida = SalidaSector {
             sector: sector,
             centros: cves,
+            iteracion: iteracion,
+            ruta: String::from(ruta),
             writer: writer,
         };
+
+        let mut encabezado = Vec::new();
+        if salida.iteracion {
+            encabezado.push(String::from("iteracion"));
+        }
+        encabezado.extend(salida.centros.iter().cloned());
     
-        salida.writer.write_record(&salida.centros)?;
+        salida.writer.write_record(&encabezado)?;
     
         Ok(salida)
     }
 
-    pub fn escribir_registro(&mut self, celdas: &HashMap<String, Celda>) -> Result<(), Box<dyn Error>> {
+    pub fn escribir_registro(&mut self, t: usize, celdas: &HashMap<String, Celda>) -> Result<(), Box<dyn Error>> {
 
-        let sizes: Vec<String> = self.centros.iter().map(|centro| {
+        let mut sizes: Vec<String> = Vec::new();
+        if self.iteracion {
+            sizes.push(t.to_string());
+        }
+
+        sizes.extend(self.centros.iter().map(|centro| {
             celdas.get(centro).unwrap().size_of_activity(self.sector).unwrap().to_string()
-        }).collect();
+        }));
 
         self.writer.write_record(&sizes)?;
 
@@ -193,9 +245,198 @@ impl<'a> SalidaSector<'a> {
 
         Ok(())
     }
+
+    pub fn ruta(&self) -> String {
+        self.ruta.to_owned()
+    }
+}
+
+pub struct SalidaLarga {
+    writer: csv::Writer<File>,
+}
+
+impl SalidaLarga {
+    pub fn get_salida_larga(ruta: &str) -> Result<SalidaLarga, Box<dyn Error>> {
+
+        let mut writer = csv::Writer::from_path(ruta)?;
+
+        writer.write_record([
+            "iteracion", "sector", "CVE", "size", "revenue", "cost", "margin", "share", "poblacion"
+        ])?;
+
+        Ok(SalidaLarga {
+            writer: writer,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) 
+        -> Result<(), Box<dyn Error>> {
+
+        let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+        cves_sectores.sort();
+
+        for cve_sector in cves_sectores {
+            let sector = &sectores[cve_sector];
+
+            let mut centros: Vec<&Celda> = celdas.values().filter(|celda| {
+                celda.get_activity(sector).is_some()
+            }).collect();
+            centros.sort_by_key(|celda| celda.cve());
+
+            let mut economia = Vec::new();
+            for celda in centros.iter() {
+                let revenue = celdas.member_revenue(celda, sector)?;
+                let cost = celdas.member_cost(celda, sector)?;
+                economia.push((revenue, cost));
+            }
+
+            let total: f64 = economia.iter().map(|(revenue, _)| revenue).sum();
+
+            for (celda, (revenue, cost)) in centros.iter().zip(economia.iter()) {
+                self.writer.write_record(&[
+                    t.to_string(),
+                    cve_sector.to_owned(),
+                    celda.cve(),
+                    celda.size_of_activity(sector)?.to_string(),
+                    revenue.to_string(),
+                    cost.to_string(),
+                    (revenue - cost).to_string(),
+                    (revenue / total).to_string(),
+                    celda.population().to_string(),
+                ])?;
+            };
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
 }
 
-pub fn get_salida<'a>(sectores: &'a HashMap<String, Sector>, celdas: &HashMap<String, Celda>, directorio: &str) 
+pub struct SalidaAreas {
+    writer: csv::Writer<File>,
+}
+
+impl SalidaAreas {
+    pub fn get_salida_areas(ruta: &str) -> Result<SalidaAreas, Box<dyn Error>> {
+
+        let mut writer = csv::Writer::from_path(ruta)?;
+
+        writer.write_record(["iteracion", "sector", "CVE", "centro", "flux"])?;
+
+        Ok(SalidaAreas {
+            writer: writer,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) 
+        -> Result<(), Box<dyn Error>> {
+
+        let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+        cves_sectores.sort();
+
+        let mut consumidores: Vec<&Celda> = celdas.values().collect();
+        consumidores.sort_by_key(|celda| celda.cve());
+
+        for cve_sector in cves_sectores {
+            let sector = &sectores[cve_sector];
+
+            for consumidor in consumidores.iter() {
+                let dominante = celdas.consumer_fluxes(consumidor, sector).into_iter()
+                    .filter(|(_, flux)| *flux > 0.0)
+                    .fold(None, |mejor: Option<(String, f64)>, (cve, flux)| {
+                        match mejor {
+                            Some((ref cve_mejor, flux_mejor)) if flux_mejor > flux || (flux_mejor == flux && *cve_mejor <= cve) => mejor,
+                            _ => Some((cve, flux))
+                        }
+                    });
+
+                let (centro, flux) = match dominante {
+                    Some((centro, flux)) => (centro, flux.to_string()),
+                    None => (String::new(), String::new())
+                };
+
+                self.writer.write_record(&[
+                    t.to_string(),
+                    cve_sector.to_owned(),
+                    consumidor.cve(),
+                    centro,
+                    flux,
+                ])?;
+            };
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+pub struct SalidaPoblacion {
+    completa: bool,
+    previas: HashMap<String, f64>,
+    writer: csv::Writer<File>,
+}
+
+impl SalidaPoblacion {
+    pub fn get_salida_poblacion(celdas: &HashMap<String, Celda>, ruta: &str, completa: bool) -> Result<SalidaPoblacion, Box<dyn Error>> {
+
+        let previas = celdas.iter().map(|(cve, celda)| (cve.to_owned(), celda.population())).collect();
+
+        let mut writer = csv::Writer::from_path(ruta)?;
+
+        writer.write_record(["iteracion", "CVE", "poblacion"])?;
+
+        Ok(SalidaPoblacion {
+            completa: completa,
+            previas: previas,
+            writer: writer,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, celdas: &HashMap<String, Celda>) -> Result<(), Box<dyn Error>> {
+
+        let mut cves: Vec<&String> = celdas.keys().collect();
+        cves.sort();
+
+        for cve in cves {
+            let poblacion = celdas[cve].population();
+
+            let cambio = match self.previas.get(cve) {
+                Some(previa) => *previa != poblacion,
+                None => true
+            };
+
+            if self.completa || cambio {
+                self.writer.write_record(&[
+                    t.to_string(),
+                    cve.to_owned(),
+                    poblacion.to_string(),
+                ])?;
+            }
+
+            self.previas.insert(cve.to_owned(), poblacion);
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+
+        Ok(())
+    }
+}
+
+pub fn get_salida<'a>(sectores: &'a HashMap<String, Sector>, celdas: &HashMap<String, Celda>, directorio: &str, iteracion: bool) 
     -> Result<HashMap<String, SalidaSector<'a>>, Box<dyn Error>> {
 
         let mut salida = HashMap::new();
@@ -206,18 +447,18 @@ pub fn get_salida<'a>(sectores: &'a HashMap<String, Sector>, celdas: &HashMap<St
             ruta.push_str(&cve);
             ruta.push_str(".csv");
 
-            salida.insert(cve.to_owned(),SalidaSector::get_salida_sector(sector, celdas, &ruta)?);
+            salida.insert(cve.to_owned(),SalidaSector::get_salida_sector(sector, celdas, &ruta, iteracion)?);
 
         };
 
         Ok(salida)
 }
 
-pub fn escribir_iteracion(salida: &mut HashMap<String, SalidaSector>, celdas: &HashMap<String, Celda>) 
+pub fn escribir_iteracion(salida: &mut HashMap<String, SalidaSector>, celdas: &HashMap<String, Celda>, t: usize) 
     -> Result<(), Box<dyn Error>> {
 
         for (_, salida_sector) in salida {
-            salida_sector.escribir_registro(celdas)?;
+            salida_sector.escribir_registro(t, celdas)?;
         };
 
         Ok(())
@@ -232,6 +473,14 @@ pub fn flush_salida(salida: &mut HashMap<String, SalidaSector>) -> Result<(), Bo
     Ok(())
 }
 
+pub fn rutas_salida(salida: &HashMap<String, SalidaSector>) -> Vec<String> {
+
+    let mut rutas: Vec<String> = salida.values().map(|salida_sector| salida_sector.ruta()).collect();
+    rutas.sort();
+
+    rutas
+}
+
 pub fn escribir_topologia(celdas: &HashMap<String, Celda>, ruta: &str) -> Result<(), Box<dyn Error>> {
     let mut wtr_cells = csv::Writer::from_path(ruta)?;
 
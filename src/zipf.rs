@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use crate::centros::{Sector, Celda};
+
+pub struct AjusteZipf {
+    pub n: usize,
+    pub exponente: f64,
+    pub error: f64,
+    pub inferior: f64,
+    pub superior: f64,
+}
+
+pub fn curva_rango_tamano(celdas: &HashMap<String, Celda>, sector: &Sector, umbral: f64) -> Vec<(usize, String, f64)> {
+
+    let mut vivos: Vec<(String, f64)> = celdas.iter().filter_map(|(cve, celda)| {
+        match celda.size_of_activity(sector) {
+            Ok(size) if size > umbral => Some((cve.to_owned(), size)),
+            _ => None
+        }
+    }).collect();
+
+    vivos.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    vivos.into_iter().enumerate().map(|(rango, (cve, size))| (rango + 1, cve, size)).collect()
+}
+
+fn ordenados(tamanos: &[f64], umbral: f64) -> Vec<f64> {
+    let mut vivos: Vec<f64> = tamanos.iter().cloned().filter(|size| *size > umbral).collect();
+    vivos.sort_by(|a, b| b.total_cmp(a));
+
+    vivos
+}
+
+fn log_rango_tamano(vivos: &[f64]) -> Vec<(f64, f64)> {
+    vivos.iter().enumerate().map(|(rango, size)| (((rango + 1) as f64).ln(), size.ln())).collect()
+}
+
+fn recta(puntos: &[(f64, f64)]) -> (f64, f64, f64) {
+    let n = puntos.len() as f64;
+    let media_x = puntos.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let media_y = puntos.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let sxy: f64 = puntos.iter().map(|(x, y)| (x - media_x) * (y - media_y)).sum();
+    let sxx: f64 = puntos.iter().map(|(x, _)| (x - media_x) * (x - media_x)).sum();
+
+    let pendiente = sxy / sxx;
+
+    (pendiente, media_y - pendiente * media_x, sxx)
+}
+
+pub fn pendiente_ols(tamanos: &[f64], umbral: f64) -> Option<f64> {
+
+    let vivos = ordenados(tamanos, umbral);
+
+    if vivos.len() < 2 {
+        return None
+    }
+
+    let (pendiente, _, _) = recta(&log_rango_tamano(&vivos));
+
+    Some(pendiente)
+}
+
+// Aproximación racional de Acklam; el error relativo es menor que 1.2e-9.
+fn cuantil_normal(p: f64) -> f64 {
+    let a = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+             1.383577518672690e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    let b = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+             6.680131188771972e+01, -1.328068155288572e+01];
+    let c = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+             -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    let d = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+             3.754408661907416e+00];
+
+    let p_bajo = 0.02425;
+
+    if p <= 0.0 || p >= 1.0 {
+        return f64::NAN
+    }
+
+    if p < p_bajo {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5]) /
+            ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1.0)
+    } else if p <= 1.0 - p_bajo {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((a[0] * r + a[1]) * r + a[2]) * r + a[3]) * r + a[4]) * r + a[5]) * q /
+            (((((b[0] * r + b[1]) * r + b[2]) * r + b[3]) * r + b[4]) * r + 1.0)
+    } else {
+        -cuantil_normal(1.0 - p)
+    }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    let coeficientes = [676.5203681218851, -1259.1392167224028, 771.32342877765313, -176.61503916999185,
+                        12.507343278686905, -0.13857109526572012, 9.9843695780195716e-6, 1.5056327351493116e-7];
+
+    if x < 0.5 {
+        return (std::f64::consts::PI / (std::f64::consts::PI * x).sin()).ln() - ln_gamma(1.0 - x)
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let serie = coeficientes.iter().enumerate().fold(0.99999999999980993, |suma, (i, c)| suma + c / (x + i as f64 + 1.0));
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + serie.ln()
+}
+
+fn fraccion_beta(a: f64, b: f64, x: f64) -> f64 {
+    let minimo = 1e-300;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < minimo {
+        d = minimo;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+
+        for numerador in [m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+                          -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0))] {
+            d = 1.0 + numerador * d;
+            if d.abs() < minimo {
+                d = minimo;
+            }
+            c = 1.0 + numerador / c;
+            if c.abs() < minimo {
+                c = minimo;
+            }
+            d = 1.0 / d;
+            h = h * d * c;
+        };
+
+        if (d * c - 1.0).abs() < 1e-15 {
+            break
+        }
+    };
+
+    h
+}
+
+fn beta_regularizada(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0
+    }
+
+    if x >= 1.0 {
+        return 1.0
+    }
+
+    let frente = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    match x < (a + 1.0) / (a + b + 2.0) {
+        true => frente * fraccion_beta(a, b, x) / a,
+        false => 1.0 - frente * fraccion_beta(b, a, 1.0 - x) / b
+    }
+}
+
+fn distribucion_t(t: f64, grados: f64) -> f64 {
+    let cola = 0.5 * beta_regularizada(grados / 2.0, 0.5, grados / (grados + t * t));
+
+    match t > 0.0 {
+        true => 1.0 - cola,
+        false => cola
+    }
+}
+
+fn cuantil_t(p: f64, grados: f64) -> f64 {
+    if !(grados > 0.0) || !(p > 0.0 && p < 1.0) {
+        return f64::NAN
+    }
+
+    let mut inferior = -1.0;
+    let mut superior = 1.0;
+
+    while distribucion_t(inferior, grados) > p {
+        inferior *= 2.0;
+    };
+
+    while distribucion_t(superior, grados) < p {
+        superior *= 2.0;
+    };
+
+    for _ in 0..200 {
+        let medio = 0.5 * (inferior + superior);
+
+        match distribucion_t(medio, grados) < p {
+            true => inferior = medio,
+            false => superior = medio
+        };
+
+        if superior - inferior <= 1e-12 * medio.abs().max(1.0) {
+            break
+        }
+    };
+
+    0.5 * (inferior + superior)
+}
+
+pub fn ajuste_ols(tamanos: &[f64], umbral: f64, nivel: f64) -> Option<AjusteZipf> {
+
+    let vivos = ordenados(tamanos, umbral);
+
+    if vivos.len() < 3 {
+        return None
+    }
+
+    let puntos = log_rango_tamano(&vivos);
+
+    let n = puntos.len() as f64;
+    let (pendiente, ordenada, sxx) = recta(&puntos);
+
+    let residuos: f64 = puntos.iter().map(|(x, y)| {
+        let e = y - ordenada - pendiente * x;
+        e * e
+    }).sum();
+
+    let error = (residuos / (n - 2.0) / sxx).sqrt();
+    let t = cuantil_t(0.5 + nivel / 2.0, n - 2.0);
+
+    Some(AjusteZipf {
+        n: puntos.len(),
+        exponente: -pendiente,
+        error: error,
+        inferior: -pendiente - t * error,
+        superior: -pendiente + t * error,
+    })
+}
+
+pub fn ajuste_mv(tamanos: &[f64], umbral: f64, nivel: f64) -> Option<AjusteZipf> {
+
+    let vivos = ordenados(tamanos, umbral);
+
+    if vivos.len() < 2 {
+        return None
+    }
+
+    let minimo = vivos[vivos.len() - 1];
+    let n = vivos.len() as f64;
+    let suma: f64 = vivos.iter().map(|size| (size / minimo).ln()).sum();
+
+    if suma <= 0.0 {
+        return None
+    }
+
+    let alfa = 1.0 + n / suma;
+    let exponente = 1.0 / (alfa - 1.0);
+    let error = exponente / n.sqrt();
+    let z = cuantil_normal(0.5 + nivel / 2.0);
+
+    Some(AjusteZipf {
+        n: vivos.len(),
+        exponente: exponente,
+        error: error,
+        inferior: exponente - z * error,
+        superior: exponente + z * error,
+    })
+}
+
+pub struct SalidaZipf {
+    umbral: f64,
+    nivel: f64,
+    ajustes: csv::Writer<File>,
+    curvas: Option<csv::Writer<File>>,
+}
+
+impl SalidaZipf {
+    pub fn get_salida_zipf(ruta_ajustes: &str, ruta_curvas: Option<&str>, umbral: f64, nivel: f64) -> Result<SalidaZipf, Box<dyn Error>> {
+
+        let mut ajustes = csv::Writer::from_path(ruta_ajustes)?;
+        ajustes.write_record(["iteracion", "sector", "metodo", "n", "exponente", "error", "inferior", "superior"])?;
+
+        let curvas = match ruta_curvas {
+            Some(ruta) => {
+                let mut curvas = csv::Writer::from_path(ruta)?;
+                curvas.write_record(["iteracion", "sector", "rango", "CVE", "size"])?;
+                Some(curvas)
+            },
+            None => None
+        };
+
+        Ok(SalidaZipf {
+            umbral: umbral,
+            nivel: nivel,
+            ajustes: ajustes,
+            curvas: curvas,
+        })
+    }
+
+    pub fn escribir_registro(&mut self, t: usize, sectores: &HashMap<String, Sector>, celdas: &HashMap<String, Celda>) 
+        -> Result<(), Box<dyn Error>> {
+
+        let mut cves_sectores: Vec<&String> = sectores.keys().collect();
+        cves_sectores.sort();
+
+        for cve_sector in cves_sectores {
+            let sector = &sectores[cve_sector];
+            let curva = curva_rango_tamano(celdas, sector, self.umbral);
+            let tamanos: Vec<f64> = curva.iter().map(|(_, _, size)| *size).collect();
+
+            let metodos = vec![
+                ("ols", ajuste_ols(&tamanos, self.umbral, self.nivel)),
+                ("mv", ajuste_mv(&tamanos, self.umbral, self.nivel)),
+            ];
+
+            for (metodo, ajuste) in metodos {
+                if let Some(ajuste) = ajuste {
+                    self.ajustes.write_record(&[
+                        t.to_string(),
+                        cve_sector.to_owned(),
+                        metodo.to_owned(),
+                        ajuste.n.to_string(),
+                        ajuste.exponente.to_string(),
+                        ajuste.error.to_string(),
+                        ajuste.inferior.to_string(),
+                        ajuste.superior.to_string(),
+                    ])?;
+                }
+            };
+
+            if let Some(curvas) = self.curvas.as_mut() {
+                for (rango, cve, size) in curva.iter() {
+                    curvas.write_record(&[
+                        t.to_string(),
+                        cve_sector.to_owned(),
+                        rango.to_string(),
+                        cve.to_owned(),
+                        size.to_string(),
+                    ])?;
+                };
+            }
+        };
+
+        Ok(())
+    }
+
+    pub fn flush_writer(&mut self) -> Result<(), Box<dyn Error>> {
+        self.ajustes.flush()?;
+
+        if let Some(curvas) = self.curvas.as_mut() {
+            curvas.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuantiles_normales_conocidos() {
+        assert!(cuantil_normal(0.5).abs() < 1e-9);
+        assert!((cuantil_normal(0.975) - 1.959963984540054).abs() < 1e-8);
+        assert!((cuantil_normal(0.01) + 2.326347874040841).abs() < 1e-8);
+        assert!(cuantil_normal(1.0).is_nan());
+    }
+
+    #[test]
+    fn cuantiles_t_conocidos() {
+        assert!((cuantil_t(0.975, 1.0) - 12.706204736174707).abs() < 1e-6);
+        assert!((cuantil_t(0.975, 3.0) - 3.182446305284263).abs() < 1e-6);
+        assert!((cuantil_t(0.95, 10.0) - 1.812461122811676).abs() < 1e-6);
+        assert!((cuantil_t(0.025, 3.0) + 3.182446305284263).abs() < 1e-6);
+        assert!((cuantil_t(0.975, 1e6) - cuantil_normal(0.975)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ajuste_de_una_ley_de_zipf_exacta() {
+        let tamanos: Vec<f64> = (1..=50).map(|rango| 1000.0 / rango as f64).collect();
+        let ajuste = ajuste_ols(&tamanos, 0.0, 0.95).unwrap();
+
+        assert_eq!(ajuste.n, 50);
+        assert!((ajuste.exponente - 1.0).abs() < 1e-9);
+        assert!(ajuste.inferior <= ajuste.exponente && ajuste.exponente <= ajuste.superior);
+    }
+}